@@ -57,6 +57,10 @@ pub struct App {
     /// Grip that is more permanent.
     pub(crate) toggle_grip: Grip,
 
+    /// File to save a screenshot to the next time the puzzle is drawn.
+    #[cfg(not(target_arch = "wasm32"))]
+    screenshot_path: Option<PathBuf>,
+
     status_msg: String,
 }
 impl App {
@@ -81,6 +85,9 @@ impl App {
             transient_grips: HashMap::default(),
             toggle_grip: Grip::default(),
 
+            #[cfg(not(target_arch = "wasm32"))]
+            screenshot_path: None,
+
             status_msg: String::default(),
         };
 
@@ -108,6 +115,10 @@ impl App {
     pub(crate) fn draw_puzzle(&mut self, gfx: &mut GraphicsState) -> Option<wgpu::TextureView> {
         let ret = crate::render::draw_puzzle(self, gfx, self.force_redraw);
         self.force_redraw = false;
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = self.screenshot_path.take() {
+            self.try_save_screenshot(gfx, &path);
+        }
         ret
     }
 
@@ -168,6 +179,15 @@ impl App {
                     }
                 }
                 Command::SaveAs => unsupported_on_web! { self; self.try_save_puzzle_as() },
+                Command::SaveScreenshot => {
+                    unsupported_on_web! {
+                        self;
+                        if let Some(path) = screenshot_file_dialog().save_file() {
+                            self.screenshot_path = Some(path);
+                        }
+                    }
+                }
+                Command::ExportObj => unsupported_on_web! { self; self.try_export_obj() },
 
                 Command::Exit => {
                     unsupported_on_web! {
@@ -180,13 +200,20 @@ impl App {
 
                 Command::CopyHscLog => self.try_copy_puzzle(LogFileFormat::Hsc, &mut response),
                 Command::CopyMc4dLog => self.try_copy_puzzle(LogFileFormat::Mc4d, &mut response),
+                Command::CopyShareCode => {
+                    let snapshot = self.puzzle.latest().snapshot();
+                    response.copy_string = Some(crate::logfile::encode_share_code(&snapshot));
+                    self.set_status_ok("Copied share code");
+                }
                 Command::PasteLog => response.request_paste = true,
 
                 Command::Undo => {
-                    self.puzzle.undo()?;
+                    let blocking = self.prefs.interaction.blocking_twist_animations;
+                    self.puzzle.undo_from_input(blocking)?;
                 }
                 Command::Redo => {
-                    self.puzzle.redo()?;
+                    let blocking = self.prefs.interaction.blocking_twist_animations;
+                    self.puzzle.redo_from_input(blocking)?;
                 }
                 Command::Reset => {
                     if self.confirm_discard_changes("reset puzzle") {
@@ -231,7 +258,8 @@ impl App {
             },
 
             AppEvent::Twist(twist) => {
-                self.puzzle.twist(twist)?;
+                let blocking = self.prefs.interaction.blocking_twist_animations;
+                self.puzzle.twist_from_input(twist, blocking)?;
             }
 
             AppEvent::Click(mouse_button) => {
@@ -242,9 +270,11 @@ impl App {
                 });
                 if let Some(bind) = matching_mousebind {
                     match bind.command {
-                        PuzzleMouseCommand::TwistCw => self.click_twist(|tw| tw.cw)?,
-                        PuzzleMouseCommand::TwistCcw => self.click_twist(|tw| tw.ccw)?,
-                        PuzzleMouseCommand::Recenter => self.click_twist(|tw| tw.recenter)?,
+                        PuzzleMouseCommand::TwistCw => self.click_twist(|tw| tw.cw, true)?,
+                        PuzzleMouseCommand::TwistCcw => self.click_twist(|tw| tw.ccw, true)?,
+                        PuzzleMouseCommand::Recenter => {
+                            self.click_twist(|tw| tw.recenter, false)?
+                        }
                         PuzzleMouseCommand::SelectPiece => {
                             if let Some(sticker) = self.puzzle.hovered_sticker() {
                                 self.puzzle.toggle_select(sticker);
@@ -258,14 +288,22 @@ impl App {
             }
             AppEvent::Drag(delta) => {
                 let delta = delta * self.prefs.interaction.drag_sensitivity * 360.0;
-                self.puzzle.freeze_view_angle_offset();
-                self.puzzle
-                    .add_view_angle_offset([delta.x, delta.y], self.prefs.view(self.puzzle.ty()));
+                if self.pressed_modifiers().alt() {
+                    // Roll the view around the view direction. This is saved
+                    // as part of the view angle, so it is not realigned.
+                    self.prefs.view_mut(self.puzzle.ty()).add_roll(-delta.x);
+                    self.prefs.needs_save = true;
+                    self.request_redraw_puzzle();
+                } else {
+                    self.puzzle.freeze_view_angle_offset();
+                    self.puzzle.add_view_angle_offset(
+                        [delta.x, delta.y],
+                        self.prefs.view(self.puzzle.ty()),
+                    );
+                }
             }
             AppEvent::DragReleased => {
-                if self.prefs.interaction.realign_on_release {
-                    self.puzzle.unfreeze_view_angle_offset();
-                }
+                self.puzzle.release_view_angle_offset(&self.prefs.interaction);
             }
 
             AppEvent::StatusError(msg) => return Err(msg),
@@ -353,15 +391,22 @@ impl App {
         }
     }
 
+    /// Performs a twist on the hovered sticker. If `reversible` is `true`, then
+    /// the twist direction respects per-axis reversal.
     fn click_twist(
         &mut self,
         get_twist: fn(ClickTwists) -> Option<Twist>,
+        reversible: bool,
     ) -> Result<(), &'static str> {
         if self.puzzle.current_twist().is_none() {
             if let Some(twists) = self.puzzle.hovered_twists() {
                 if let Some(mut t) = get_twist(twists) {
                     t.layers = self.gripped_layers(t.layers);
-                    self.puzzle.twist(t)?;
+                    if reversible {
+                        t = self.puzzle.apply_axis_reversal(t, &self.prefs.interaction);
+                    }
+                    let blocking = self.prefs.interaction.blocking_twist_animations;
+                    self.puzzle.twist_from_input(t, blocking)?;
                 }
             }
         }
@@ -690,11 +735,15 @@ impl App {
         direction: &str,
         layers: LayerMask,
     ) -> Result<(), String> {
-        self.event(AppEvent::Twist(Twist {
+        let twist = Twist {
             axis: self.gripped_twist_axis(twist_axis)?,
             direction: self.twist_direction_from_name(direction)?,
             layers: self.gripped_layers(layers),
-        }));
+        };
+        let twist = self
+            .puzzle
+            .apply_axis_reversal(twist, &self.prefs.interaction);
+        self.event(AppEvent::Twist(twist));
         Ok(())
     }
     pub(crate) fn do_recenter(&self, twist_axis: Option<&str>) -> Result<(), String> {
@@ -766,13 +815,9 @@ impl App {
     }
 
     fn confirm_discard_changes(&mut self, action: &str) -> bool {
-        let mut needs_save = self.puzzle.is_unsaved();
-
-        if self.prefs.interaction.confirm_discard_only_when_scrambled
-            && !self.puzzle.has_been_fully_scrambled()
-        {
-            needs_save = false;
-        }
+        let needs_save = self
+            .puzzle
+            .should_confirm_discard(self.prefs.interaction.confirm_discard_only_when_scrambled);
 
         let confirm = !needs_save
             || rfd::MessageDialog::new()
@@ -788,6 +833,23 @@ impl App {
     }
 
     fn try_paste_puzzle(&mut self, log_file_contents: &str) {
+        if let Ok(snapshot) = crate::logfile::decode_share_code(log_file_contents) {
+            match PuzzleController::from_snapshot(&snapshot) {
+                Ok(puzzle) => {
+                    if self.confirm_discard_changes("load puzzle from share code") {
+                        self.puzzle = puzzle;
+
+                        self.set_status_ok("Loaded puzzle from share code");
+
+                        self.prefs.log_file = None;
+                        self.prefs.needs_save = true;
+                    }
+                }
+                Err(e) => self.set_status_err(format!("Unable to load share code: {e}")),
+            }
+            return;
+        }
+
         match crate::logfile::deserialize(log_file_contents) {
             Ok((puzzle, warnings)) => {
                 if self.confirm_load_puzzle(&warnings) {
@@ -857,6 +919,50 @@ impl App {
         }
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    fn try_save_screenshot(&mut self, gfx: &GraphicsState, path: &Path) {
+        let size = self.prefs.gfx.screenshot_size(
+            self.puzzle_texture_size,
+            gfx.device.limits().max_texture_dimension_2d,
+        );
+        let transparent = self.prefs.gfx.screenshot_transparent;
+        let result = crate::render::capture_puzzle_image(
+            gfx,
+            &mut self.puzzle,
+            &self.prefs,
+            size,
+            transparent,
+        )
+        .and_then(|png_bytes| std::fs::write(path, png_bytes).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => self.set_status_ok(format!("Saved screenshot to {}", path.display())),
+            Err(e) => show_error_dialog("Unable to save screenshot", e),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn try_export_obj(&mut self) {
+        let Some(path) = obj_file_dialog().save_file() else {
+            return;
+        };
+        let mtl_path = path.with_extension("mtl");
+        let mtl_file_name = mtl_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let ty = self.puzzle.ty();
+        let view_prefs = self.puzzle.view_prefs(&self.prefs);
+        let params = StickerGeometryParams::new(&view_prefs, ty, None, cgmath::One::one());
+        let face_colors = self.prefs.colors.face_colors_list(ty);
+        let model = ObjModel::new(self.puzzle.latest(), params, &face_colors, &mtl_file_name);
+
+        match std::fs::write(&path, model.obj).and_then(|()| std::fs::write(&mtl_path, model.mtl)) {
+            Ok(()) => self.set_status_ok(format!("Exported model to {}", path.display())),
+            Err(e) => show_error_dialog("Unable to export model", e),
+        }
+    }
+
     #[cfg(target_arch = "wasm32")]
     const LOCAL_STORAGE_KEY: &str = "hyperspeedcube_puzzle_log";
     #[cfg(target_arch = "wasm32")]
@@ -963,6 +1069,14 @@ fn file_dialog() -> rfd::FileDialog {
         .add_filter("All files", &["*"])
 }
 #[cfg(not(target_arch = "wasm32"))]
+fn screenshot_file_dialog() -> rfd::FileDialog {
+    rfd::FileDialog::new().add_filter("PNG images", &["png"])
+}
+#[cfg(not(target_arch = "wasm32"))]
+fn obj_file_dialog() -> rfd::FileDialog {
+    rfd::FileDialog::new().add_filter("Wavefront OBJ models", &["obj"])
+}
+#[cfg(not(target_arch = "wasm32"))]
 fn show_error_dialog(title: &str, e: impl fmt::Display) {
     rfd::MessageDialog::new()
         .set_title(title)
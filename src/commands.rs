@@ -19,11 +19,14 @@ pub enum Command {
     Open,
     Save,
     SaveAs,
+    SaveScreenshot,
+    ExportObj,
     Exit,
 
     // File menu (web)
     CopyHscLog,
     CopyMc4dLog,
+    CopyShareCode,
     PasteLog,
 
     // Edit menu
@@ -50,10 +53,13 @@ impl Command {
             Command::Open => "🗁".to_owned(),
             Command::Save => "💾".to_owned(),
             Command::SaveAs => "Save As".to_owned(),
+            Command::SaveScreenshot => "📷".to_owned(),
+            Command::ExportObj => "Export OBJ".to_owned(),
             Command::Exit => "Exit".to_owned(),
 
             Command::CopyHscLog => "🗐".to_owned(),
             Command::CopyMc4dLog => "🗐".to_owned(),
+            Command::CopyShareCode => "🗐".to_owned(),
             Command::PasteLog => "📋".to_owned(),
 
             Command::Undo => "⮪".to_owned(),
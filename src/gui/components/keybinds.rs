@@ -243,10 +243,13 @@ impl egui::Widget for CommandSelectWidget<'_, GlobalKeybindsAccessor> {
                     "Open..." => Cmd::Open,
                     "Save" => Cmd::Save,
                     "Save as..." => Cmd::SaveAs,
+                    "Save screenshot..." => Cmd::SaveScreenshot,
+                    "Export OBJ..." => Cmd::ExportObj,
                     "Exit" => Cmd::Exit,
 
                     "Copy .hsc" => Cmd::CopyHscLog,
                     "Copy .log" => Cmd::CopyMc4dLog,
+                    "Copy share code" => Cmd::CopyShareCode,
                     "Paste .log" => Cmd::PasteLog,
 
                     "Undo" => Cmd::Undo,
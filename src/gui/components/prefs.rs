@@ -1,10 +1,14 @@
 use egui::NumExt;
+use std::borrow::Cow;
+use strum::IntoEnumIterator;
 
 use crate::app::App;
-use crate::gui::components::{with_reset_button, PresetsUi, WidgetWithReset};
+use crate::gui::components::{with_reset_button, FancyComboBox, PresetsUi, WidgetWithReset};
 use crate::gui::ext::*;
 use crate::gui::util::Access;
-use crate::preferences::{OpacityPreferences, DEFAULT_PREFS};
+use crate::preferences::{
+    AxisInteractionPreferences, OpacityPreferences, OutlineColorMode, StereoMode, DEFAULT_PREFS,
+};
 use crate::puzzle::{traits::*, Face, ProjectionType};
 use crate::serde_impl::hex_color;
 
@@ -145,6 +149,44 @@ pub fn build_colors_section(ui: &mut egui::Ui, app: &mut App) {
 
     prefs_ui.ui.separator();
 
+    prefs_ui.ui.strong("Stickers");
+    let selection = app.puzzle.selection();
+    let paint_color_id = prefs_ui.ui.id().with("sticker_paint_color");
+    let mut paint_color = prefs_ui
+        .ui
+        .data()
+        .get_temp(paint_color_id)
+        .unwrap_or(egui::Color32::WHITE);
+    prefs_ui.ui.horizontal(|ui| {
+        ui.color_edit_button_srgba(&mut paint_color);
+        let r = ui
+            .add_enabled(!selection.is_empty(), egui::Button::new("Paint selected"))
+            .on_hover_explanation(
+                "",
+                "Overrides the color of the selected stickers, \
+                 regardless of which face they belong to.",
+            );
+        if r.clicked() {
+            for &sticker in selection {
+                prefs_ui
+                    .current
+                    .set_sticker_color(puzzle_type, sticker, Some(paint_color));
+            }
+            *prefs_ui.changed = true;
+        }
+    });
+    prefs_ui.ui.data().insert_temp(paint_color_id, paint_color);
+    let has_overrides = !prefs_ui.current.sticker_overrides[puzzle_type].is_empty();
+    let r = prefs_ui
+        .ui
+        .add_enabled(has_overrides, egui::Button::new("Clear sticker colors"));
+    if r.clicked() {
+        prefs_ui.current.sticker_overrides[puzzle_type].clear();
+        *prefs_ui.changed = true;
+    }
+
+    prefs_ui.ui.separator();
+
     prefs_ui.ui.strong("Special");
     prefs_ui.color("Background", access!(.background));
     prefs_ui.color("Blindfolded stickers", access!(.blind_face));
@@ -173,20 +215,83 @@ pub fn build_graphics_section(ui: &mut egui::Ui, app: &mut App) {
         })
         .on_hover_explanation("Frames Per Second", "Limits framerate to save power");
 
+    prefs_ui
+        .num("Render scale", access!(.render_scale), |dv| {
+            dv.fixed_decimals(2).clamp_range(0.25..=2.0_f32).speed(0.01)
+        })
+        .on_hover_explanation(
+            "",
+            "Scale factor for the resolution of the puzzle. \
+             Values greater than 1 reduce aliasing, but may \
+             worsen performance.",
+        );
+
     let is_msaa_disabled = cfg!(target_arch = "wasm32");
     prefs_ui.ui.add_enabled_ui(!is_msaa_disabled, |ui| {
-        PrefsUi { ui, ..prefs_ui }
-            .checkbox("MSAA", access!(.msaa))
+        PrefsUi {
+            ui,
+            current: &mut *prefs_ui.current,
+            changed: &mut *prefs_ui.changed,
+            ..prefs_ui
+        }
+        .checkbox("MSAA", access!(.msaa))
+        .on_hover_explanation(
+            "Multisample Anti-Aliasing",
+            "Makes edges less jagged, \
+             but may worsen performance.",
+        )
+        .on_disabled_hover_text(
+            "Multisample anti-aliasing \
+             is not supported on web.",
+        );
+    });
+
+    prefs_ui.ui.separator();
+
+    prefs_ui.ui.horizontal(|ui| {
+        let r = ui.add(FancyComboBox {
+            combo_box: egui::ComboBox::from_id_source(unique_id!()),
+            selected: &mut prefs_ui.current.stereo_mode,
+            options: StereoMode::iter()
+                .map(|mode| (mode, Cow::Borrowed(mode.into())))
+                .collect(),
+        });
+        *prefs_ui.changed |= r.changed();
+        ui.label("Stereo");
+    });
+    if prefs_ui.current.stereo_mode != StereoMode::Off {
+        prefs_ui
+            .num("Eye separation", access!(.stereo_eye_separation), |dv| {
+                dv.fixed_decimals(2)
+                    .clamp_range(-0.5..=0.5_f32)
+                    .speed(0.005)
+            })
             .on_hover_explanation(
-                "Multisample Anti-Aliasing",
-                "Makes edges less jagged, \
-                 but may worsen performance.",
-            )
-            .on_disabled_hover_text(
-                "Multisample anti-aliasing \
-                 is not supported on web.",
+                "",
+                "Distance between the eyes, relative to the \
+                 size of the puzzle. Negative values swap the \
+                 eyes, for cross-eyed viewing.",
             );
-    });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        prefs_ui.ui.separator();
+
+        prefs_ui
+            .num("Screenshot scale", access!(.screenshot_scale), |dv| {
+                dv.fixed_decimals(2).clamp_range(0.25..=8.0_f32).speed(0.01)
+            })
+            .on_hover_explanation(
+                "",
+                "Resolution of saved screenshots, \
+                 relative to the size of the puzzle view.",
+            );
+        prefs_ui.checkbox(
+            "Transparent screenshot background",
+            access!(.screenshot_transparent),
+        );
+    }
 
     prefs.needs_save |= changed;
     if changed {
@@ -194,6 +299,7 @@ pub fn build_graphics_section(ui: &mut egui::Ui, app: &mut App) {
     }
 }
 pub fn build_interaction_section(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
     let prefs = &mut app.prefs;
 
     let mut changed = false;
@@ -244,6 +350,34 @@ pub fn build_interaction_section(ui: &mut egui::Ui, app: &mut App) {
              similar orientation, not the original. This \
              adds a full-puzzle rotation to the undo history.",
         );
+    prefs_ui
+        .angle(
+            "Smart realign threshold",
+            access!(.smart_realign_threshold),
+            |dv| dv.clamp_range(0.0..=180.0),
+        )
+        .on_hover_explanation(
+            "",
+            "Smart realign only snaps to a similar orientation \
+             when the puzzle is within this angle of it. \
+             Otherwise the puzzle snaps back to the original.",
+        );
+
+    prefs_ui
+        .checkbox("Preview hovered twist", access!(.preview_hovered_twist))
+        .on_hover_explanation(
+            "",
+            "When enabled, hovering over a sticker shows a \
+             translucent preview of the pieces after the \
+             twist performed by clicking on it.",
+        );
+    prefs_ui
+        .checkbox("Show adjacent faces", access!(.show_adjacent_faces))
+        .on_hover_explanation(
+            "",
+            "When enabled, hovering over a sticker shows a \
+             tooltip listing the faces adjacent to its face.",
+        );
 
     prefs_ui.ui.separator();
 
@@ -256,6 +390,17 @@ pub fn build_interaction_section(ui: &mut egui::Ui, app: &mut App) {
                  many moves are queued up. When all queued \
                  moves are complete, the twist speed resets.",
             );
+        prefs_ui
+            .checkbox(
+                "Block input during twists",
+                access!(.blocking_twist_animations),
+            )
+            .on_hover_explanation(
+                "",
+                "When enabled, twists, undos, and redos \
+                 performed while another twist is animating \
+                 are ignored instead of being queued.",
+            );
 
         let speed = prefs_ui.current.twist_duration.at_least(0.1) / 100.0; // logarithmic speed
         prefs_ui.num("Twist duration", access!(.twist_duration), |dv| {
@@ -274,6 +419,59 @@ pub fn build_interaction_section(ui: &mut egui::Ui, app: &mut App) {
             );
     });
 
+    prefs_ui.collapsing("Twist axes", |mut prefs_ui| {
+        for axis in puzzle_type.twist_axes() {
+            let global_duration = prefs_ui.current.twist_duration;
+            let overrides = &mut prefs_ui.current.axis_overrides;
+            let mut value = overrides.get(axis.name).cloned().unwrap_or_default();
+
+            let r = with_reset_button(
+                prefs_ui.ui,
+                &mut value,
+                AxisInteractionPreferences::default(),
+                "",
+                |ui, value| {
+                    ui.strong(axis.name);
+                    let mut r = ui
+                        .checkbox(&mut value.reverse_direction, "Reverse")
+                        .on_hover_explanation(
+                            "",
+                            "When enabled, clockwise and counterclockwise \
+                             twists on this axis are swapped, both from \
+                             the keyboard and from the mouse.",
+                        );
+
+                    let mut custom_duration = value.twist_duration.is_some();
+                    r = r.union(ui.checkbox(&mut custom_duration, "Duration"));
+                    if custom_duration != value.twist_duration.is_some() {
+                        value.twist_duration = custom_duration.then_some(global_duration);
+                    }
+                    if let Some(duration) = &mut value.twist_duration {
+                        let speed = duration.at_least(0.1) / 100.0; // logarithmic speed
+                        r = r.union(
+                            ui.add(
+                                egui::DragValue::new(duration)
+                                    .fixed_decimals(2)
+                                    .clamp_range(0.0..=5.0_f32)
+                                    .speed(speed),
+                            ),
+                        );
+                    }
+                    r
+                },
+            );
+
+            if r.changed() {
+                *prefs_ui.changed = true;
+                if value == AxisInteractionPreferences::default() {
+                    overrides.remove(axis.name);
+                } else {
+                    overrides.insert(axis.name.to_string(), value);
+                }
+            }
+        }
+    });
+
     prefs.needs_save |= changed;
 }
 pub fn build_outlines_section(ui: &mut egui::Ui, app: &mut App) {
@@ -288,7 +486,28 @@ pub fn build_outlines_section(ui: &mut egui::Ui, app: &mut App) {
     };
 
     prefs_ui.ui.strong("Colors");
-    prefs_ui.color("Default", access!(.default_color));
+    prefs_ui.ui.horizontal(|ui| {
+        let r = ui.add(FancyComboBox {
+            combo_box: egui::ComboBox::from_id_source(unique_id!()),
+            selected: &mut prefs_ui.current.default_color_mode,
+            options: OutlineColorMode::iter()
+                .map(|mode| (mode, Cow::Borrowed(mode.into())))
+                .collect(),
+        });
+        *prefs_ui.changed |= r.changed();
+        ui.label("Default mode");
+    });
+    if prefs_ui.current.default_color_mode == OutlineColorMode::Fixed {
+        prefs_ui.color("Default", access!(.default_color));
+    } else {
+        prefs_ui
+            .percent("Face blend", access!(.face_color_blend))
+            .on_hover_explanation(
+                "",
+                "How much to darken or lighten the face \
+                 color to get the outline color.",
+            );
+    }
     prefs_ui.color("Hidden", access!(.hidden_color));
     prefs_ui.color("Hovered", access!(.hovered_color));
     prefs_ui.color("Sel. sticker", access!(.selected_sticker_color));
@@ -417,8 +636,30 @@ pub fn build_view_section(ui: &mut egui::Ui, app: &mut App) {
             prefs_ui.angle("4D FOV", access!(.fov_4d), |dv| {
                 dv.clamp_range(1.0..=120.0).speed(0.5)
             });
+            for (i, axis) in ["X", "Y", "Z"].into_iter().enumerate() {
+                prefs_ui
+                    .num(
+                        &format!("4D camera {axis}"),
+                        access!(.camera_4d_offset[i]),
+                        |dv| dv.fixed_decimals(2).clamp_range(-1.0..=1.0).speed(0.01),
+                    )
+                    .on_hover_explanation(
+                        "",
+                        "Offset of the 4D camera, for oblique views \
+                         that reveal structure hidden in the standard \
+                         projection.",
+                    );
+            }
         }
 
+        prefs_ui
+            .checkbox("Orthographic 3D", access!(.orthographic_3d))
+            .on_hover_explanation(
+                "",
+                "Ignores the 3D FOV so that stickers \
+                 appear the same size regardless of depth.",
+            );
+
         let label = if prefs_ui.current.fov_3d == 120.0 {
             "QUAKE PRO"
         } else if prefs_ui.current.fov_3d == -120.0 {
@@ -426,15 +667,51 @@ pub fn build_view_section(ui: &mut egui::Ui, app: &mut App) {
         } else {
             "3D FOV"
         };
-        prefs_ui.angle(label, access!(.fov_3d), |dv| {
-            dv.clamp_range(-120.0..=120.0).speed(0.5)
+        let is_orthographic = prefs_ui.current.orthographic_3d;
+        prefs_ui.ui.add_enabled_ui(!is_orthographic, |ui| {
+            PrefsUi { ui, ..prefs_ui }.angle(label, access!(.fov_3d), |dv| {
+                dv.clamp_range(-120.0..=120.0).speed(0.5)
+            });
         });
     });
 
     prefs_ui.collapsing("Geometry", |mut prefs_ui| {
         if proj_ty == ProjectionType::_3D {
-            prefs_ui.checkbox("Show frontfaces", access!(.show_frontfaces));
-            prefs_ui.checkbox("Show backfaces", access!(.show_backfaces));
+            let mut is_puzzle_specific = prefs_ui.current.has_puzzle_face_visibility(puzzle_type);
+            let r = prefs_ui
+                .ui
+                .checkbox(
+                    &mut is_puzzle_specific,
+                    format!("Faces specific to {}", puzzle_type.name()),
+                )
+                .on_hover_explanation(
+                    "",
+                    "When enabled, frontface and backface \
+                     visibility is set separately for this \
+                     puzzle.",
+                );
+            if r.changed() {
+                let face_visibility =
+                    is_puzzle_specific.then(|| prefs_ui.current.face_visibility(puzzle_type));
+                prefs_ui
+                    .current
+                    .set_puzzle_face_visibility(puzzle_type, face_visibility);
+                *prefs_ui.changed = true;
+            }
+
+            if is_puzzle_specific {
+                prefs_ui.checkbox(
+                    "Show frontfaces",
+                    access!(.puzzle_face_visibility[puzzle_type].show_frontfaces),
+                );
+                prefs_ui.checkbox(
+                    "Show backfaces",
+                    access!(.puzzle_face_visibility[puzzle_type].show_backfaces),
+                );
+            } else {
+                prefs_ui.checkbox("Show frontfaces", access!(.show_frontfaces));
+                prefs_ui.checkbox("Show backfaces", access!(.show_backfaces));
+            }
         }
         if proj_ty == ProjectionType::_4D {
             prefs_ui.checkbox("Clip 4D", access!(.clip_4d));
@@ -458,6 +735,23 @@ pub fn build_view_section(ui: &mut egui::Ui, app: &mut App) {
         });
         prefs_ui.percent("Directional", access!(.light_directional));
         prefs_ui.percent("Ambient", access!(.light_ambient));
+
+        prefs_ui.ui.separator();
+
+        prefs_ui.angle("Fill pitch", access!(.fill_light_pitch), |dv| {
+            dv.clamp_range(-90.0..=90.0)
+        });
+        prefs_ui.angle("Fill yaw", access!(.fill_light_yaw), |dv| {
+            dv.clamp_range(-180.0..=180.0)
+        });
+        prefs_ui
+            .percent("Fill", access!(.fill_light))
+            .on_hover_explanation(
+                "",
+                "Brightness of a second light, which only brightens \
+                 faces that point toward it. This can make concave \
+                 shapes easier to read.",
+            );
     });
 
     prefs.needs_save |= changed;
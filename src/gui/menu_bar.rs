@@ -16,6 +16,17 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
                 command_button(ui, app, "Save", Command::Save);
                 command_button(ui, app, "Save as...", Command::SaveAs);
                 ui.separator();
+                command_button(ui, app, "Save screenshot...", Command::SaveScreenshot);
+                command_button_with_explanation(
+                    ui,
+                    app,
+                    "Export OBJ...",
+                    Command::ExportObj,
+                    "3D model of the puzzle",
+                    "Uses the current view settings, \
+                     without 3D perspective",
+                );
+                ui.separator();
             }
             command_button_with_explanation(
                 ui,
@@ -33,6 +44,14 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
                 "MC4D-compatible log file",
                 "Backwards-compatible with Magic Cube 4D",
             );
+            command_button_with_explanation(
+                ui,
+                app,
+                "Copy share code",
+                Command::CopyShareCode,
+                "Compact code for the current puzzle state",
+                "Does not include move history",
+            );
 
             #[cfg(not(target_arch = "wasm32"))]
             {
@@ -90,6 +109,7 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
         ui.menu_button("Tools", |ui| {
             windows::PIECE_FILTERS.menu_button_toggle(ui);
             windows::PUZZLE_CONTROLS.menu_button_toggle(ui);
+            windows::PUZZLE_INFO.menu_button_toggle(ui);
             windows::KEYBIND_SETS.menu_button_toggle(ui);
             windows::MODIFIER_KEYS.menu_button_toggle(ui);
         });
@@ -78,6 +78,40 @@ pub fn build(ui: &mut egui::Ui, app: &mut App, puzzle_texture_id: egui::TextureI
         app.event(AppEvent::DragReleased);
     }
 
+    // Scrub through the layers along the hovered axis by scrolling while
+    // holding alt, and show all layers again once alt is released.
+    if app.pressed_modifiers() == ModifiersState::ALT {
+        let scroll = ui.input().scroll_delta.y;
+        if r.hovered() && scroll != 0.0 {
+            let axis = app.puzzle.layer_scrub().map(|(axis, _)| axis).or_else(|| {
+                let click_twists = app.puzzle.hovered_twists()?;
+                Some(click_twists.cw?.axis)
+            });
+            if let Some(axis) = axis {
+                app.puzzle
+                    .scrub_layers(axis, if scroll < 0.0 { 1 } else { -1 });
+                app.request_redraw_puzzle();
+            }
+        }
+    } else if app.puzzle.layer_scrub().is_some() {
+        app.puzzle.stop_layer_scrub();
+        app.request_redraw_puzzle();
+    }
+
+    // Show the faces adjacent to the hovered sticker's face.
+    if app.prefs.interaction.show_adjacent_faces {
+        if let Some(adjacent_faces) = app.puzzle.hovered_sticker_adjacent_faces() {
+            use crate::puzzle::traits::*;
+
+            egui::popup::show_tooltip_at_pointer(ui.ctx(), egui::Id::new("adjacent_faces"), |ui| {
+                ui.strong("Adjacent faces");
+                for &face in adjacent_faces {
+                    ui.label(app.puzzle.info(face).name);
+                }
+            });
+        }
+    }
+
     // Show debug info for each sticker.
     #[cfg(debug_assertions)]
     if let Some(sticker) = app.puzzle.hovered_sticker() {
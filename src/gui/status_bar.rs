@@ -17,6 +17,14 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
         twist_count(ui, app);
         ui.separator();
 
+        if let Some((piece, sticker, face_name)) = app.puzzle.hovered_sticker_info() {
+            ui.label(format!(
+                "{face_name} (sticker {}, piece {})",
+                sticker.0, piece.0
+            ));
+            ui.separator();
+        }
+
         // Left-aligned segments
         ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
             if app.prefs.info.modifier_toggles {
@@ -242,10 +242,13 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
                 Command::Open => ui.label("Open"),
                 Command::Save => ui.label("Save"),
                 Command::SaveAs => ui.label("Save As"),
+                Command::SaveScreenshot => ui.label("Save screenshot"),
+                Command::ExportObj => ui.label("Export OBJ"),
                 Command::Exit => ui.label("Exit"),
 
                 Command::CopyHscLog => ui.label("Copy puzzle log (.hsc)"),
                 Command::CopyMc4dLog => ui.label("Copy puzzle log (.log)"),
+                Command::CopyShareCode => ui.label("Copy share code"),
                 Command::PasteLog => ui.label("Paste puzzle log"),
 
                 Command::Undo => ui.label("Undo"),
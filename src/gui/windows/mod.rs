@@ -6,6 +6,7 @@ mod modifier_keys;
 mod mousebinds_table;
 mod piece_filters;
 mod puzzle_controls;
+mod puzzle_info;
 mod settings;
 mod welcome;
 
@@ -18,6 +19,7 @@ pub(crate) use modifier_keys::*;
 pub(crate) use mousebinds_table::*;
 pub(crate) use piece_filters::*;
 pub(crate) use puzzle_controls::*;
+pub(crate) use puzzle_info::*;
 pub(crate) use settings::*;
 pub(crate) use welcome::*;
 
@@ -35,6 +37,7 @@ pub const ALL: &[Window] = &[
     // Tools
     KEYBINDS_REFERENCE,
     PUZZLE_CONTROLS,
+    PUZZLE_INFO,
     PIECE_FILTERS,
     MODIFIER_KEYS,
     // Settings
@@ -54,7 +57,17 @@ pub const DEBUG: Window = Window {
     location: Location::Floating,
     fixed_width: None,
     vscroll: true,
-    build: |ui, _app| {
+    build: |ui, app| {
+        let mut show_changed_pieces = app.puzzle.changed_pieces_base().is_some();
+        let r = ui
+            .checkbox(&mut show_changed_pieces, "Show only changed pieces")
+            .on_hover_text("Dims pieces that have not moved since this was enabled");
+        if r.changed() {
+            let base = show_changed_pieces.then(|| app.puzzle.snapshot());
+            app.puzzle.set_changed_pieces_base(base);
+            app.request_redraw_puzzle();
+        }
+
         let mut debug_info = std::mem::take(&mut *crate::debug::FRAME_DEBUG_INFO.lock().unwrap());
         ui.add(egui::TextEdit::multiline(&mut debug_info).code_editor());
     },
@@ -1,8 +1,15 @@
+use instant::{Duration, Instant};
+use itertools::Itertools;
+use std::task::Poll;
+
 use super::Window;
 use crate::app::App;
 use crate::gui::components::reset_button;
 use crate::puzzle::*;
 
+/// Maximum time to spend searching for a hint in each frame.
+const HINT_SEARCH_TIME_PER_FRAME: Duration = Duration::from_millis(5);
+
 pub(crate) const PUZZLE_CONTROLS: Window = Window {
     name: "Puzzle controls",
     build,
@@ -31,7 +38,10 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
         reset_button(ui, &mut app.toggle_grip.axes, Grip::default().axes, "");
         for (i, twist_axis) in puzzle_type.twist_axes().iter().enumerate() {
             let mut is_sel = grip.axes.contains(&TwistAxis(i as _));
-            let r = ui.selectable_value(&mut is_sel, true, twist_axis.name);
+            let order = puzzle_type.twist_axis_order(TwistAxis(i as _));
+            let r = ui
+                .selectable_value(&mut is_sel, true, twist_axis.name)
+                .on_hover_text(format!("{} has order {order}", twist_axis.name));
             if r.changed() {
                 app.toggle_grip
                     .toggle_axis(TwistAxis(i as _), !multi_select);
@@ -75,4 +85,69 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
             }
         });
     });
+
+    ui.separator();
+
+    // Only show the hint while the puzzle is in the state it was computed for.
+    let hint_id = unique_id!();
+    ui.horizontal(|ui| {
+        let r = ui
+            .add_enabled(!app.puzzle.is_solved(), egui::Button::new("Hint"))
+            .on_hover_text(
+                "Suggest twists toward solving the puzzle (3x3x3 only). \
+                 Hints are usually unavailable for the last layer.",
+            );
+        if r.clicked() {
+            let hint = match app.puzzle.hint_search() {
+                Some(search) => HintState::Searching(Box::new(search)),
+                None => HintState::Done(None),
+            };
+            let snapshot = app.puzzle.latest().snapshot();
+            ui.data().insert_temp(hint_id, (snapshot, hint));
+        }
+        let stored_hint = ui.data().get_temp::<(PuzzleSnapshot, HintState)>(hint_id);
+        if let Some((hint_snapshot, mut hint)) = stored_hint {
+            if hint_snapshot != app.puzzle.latest().snapshot() {
+                return;
+            }
+
+            // Search for a little while each frame so that the UI stays
+            // responsive.
+            if let HintState::Searching(search) = &mut hint {
+                let deadline = Instant::now() + HINT_SEARCH_TIME_PER_FRAME;
+                while Instant::now() < deadline {
+                    if let Poll::Ready(twists) = search.step() {
+                        hint = HintState::Done(twists);
+                        break;
+                    }
+                }
+            }
+
+            match &hint {
+                HintState::Searching(_) => {
+                    ui.label("Searching...");
+                    ui.ctx().request_repaint();
+                }
+                HintState::Done(Some(twists)) => {
+                    ui.label(
+                        twists
+                            .iter()
+                            .map(|twist| twist.to_notation_string(puzzle_type))
+                            .join(" "),
+                    );
+                }
+                HintState::Done(None) => {
+                    ui.label("No hint found");
+                }
+            }
+            ui.data().insert_temp(hint_id, (hint_snapshot, hint));
+        }
+    });
+}
+
+/// Progress of the search for a hint.
+#[derive(Debug, Clone)]
+enum HintState {
+    Searching(Box<HintSearch>),
+    Done(Option<Vec<Twist>>),
 }
@@ -0,0 +1,43 @@
+use super::Window;
+use crate::app::App;
+use crate::puzzle::PuzzleStats;
+
+pub(crate) const PUZZLE_INFO: Window = Window {
+    name: "Puzzle info",
+    build,
+    ..Window::DEFAULT
+};
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let stats = PuzzleStats::get(app.puzzle.ty());
+
+    egui::Grid::new("puzzle_info_grid")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            let mut row = |label: &str, value: usize| {
+                ui.label(label);
+                ui.label(value.to_string());
+                ui.end_row();
+            };
+            row("Pieces", stats.piece_count);
+            row("Stickers", stats.sticker_count);
+            row("Faces", stats.face_count);
+            row("Piece types", stats.piece_type_count);
+            row("Orientations", stats.symmetry_order);
+        });
+
+    ui.separator();
+
+    ui.strong("Twist axis orders");
+    egui::Grid::new("puzzle_info_axis_orders_grid")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for (name, order) in stats.axis_orders {
+                ui.label(name);
+                ui.label(order.to_string());
+                ui.end_row();
+            }
+        });
+}
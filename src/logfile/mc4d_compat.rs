@@ -152,6 +152,9 @@ impl fmt::Display for Mc4dLogFile {
 }
 impl Mc4dLogFile {
     pub fn from_puzzle(puzzle: &PuzzleController) -> Result<Self, LogFileError> {
+        if puzzle.initial_state().is_some() {
+            return Err(LogFileError::UnsupportedInitialState);
+        }
         match puzzle.ty() {
             PuzzleTypeEnum::Rubiks4D { layer_count } => Ok(Self {
                 edge_length: layer_count,
@@ -200,6 +203,7 @@ pub enum LogFileError {
     BadHeader,
     UnsupportedLogVersion,
     UnsupportedPuzzle(String),
+    UnsupportedInitialState,
     BadViewMatrix,
     MissingSep,
 }
@@ -210,6 +214,7 @@ impl fmt::Display for LogFileError {
             Self::BadHeader => write!(f, "invalid header"),
             Self::UnsupportedLogVersion => write!(f, "unsupported log version"),
             Self::UnsupportedPuzzle(name) => write!(f, "unsupported puzzle: {name}"),
+            Self::UnsupportedInitialState => write!(f, "unsupported unsolved initial state"),
             Self::BadViewMatrix => write!(f, "invalid view matrix"),
             Self::MissingSep => write!(f, "missing sep"),
         }
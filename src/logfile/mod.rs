@@ -1,4 +1,3 @@
-use anyhow::{anyhow, Context, Result};
 use bitvec::vec::BitVec;
 use num_enum::FromPrimitive;
 use serde::{Deserialize, Serialize};
@@ -11,20 +10,26 @@ use std::str::FromStr;
 use strum::IntoEnumIterator;
 
 mod mc4d_compat;
+mod share_code;
+
+pub use share_code::{decode_share_code, encode_share_code, ShareCodeError};
 
 use crate::puzzle::*;
 
 /// Loads a log file string and returns the puzzle state, along with any
 /// warnings.
-pub fn deserialize(log_file_contents: &str) -> anyhow::Result<(PuzzleController, Vec<String>)> {
+pub fn deserialize(log_file_contents: &str) -> Result<(PuzzleController, Vec<String>), LoadError> {
     if mc4d_compat::is_mc4d_log_file(log_file_contents) {
-        let puzzle = mc4d_compat::Mc4dLogFile::from_str(log_file_contents)?
+        let puzzle = mc4d_compat::Mc4dLogFile::from_str(log_file_contents)
+            .map_err(LoadError::Mc4d)?
             .to_puzzle()
-            .map_err(|e| anyhow!(e))?;
+            .map_err(LoadError::InvalidPuzzleType)?;
         let warnings = vec![];
         Ok((puzzle, warnings))
     } else {
-        serde_yaml::from_str::<LogFile>(log_file_contents)?.to_puzzle()
+        serde_yaml::from_str::<LogFile>(log_file_contents)
+            .map_err(LoadError::Yaml)?
+            .to_puzzle()
     }
 }
 
@@ -42,7 +47,7 @@ pub(crate) fn serialize(
 /// Loads a log file and returns the puzzle state, along with any warnings.
 #[cfg(not(target_arch = "wasm32"))]
 pub fn load_file(path: &Path) -> anyhow::Result<(PuzzleController, Vec<String>)> {
-    deserialize(&std::fs::read_to_string(path)?)
+    Ok(deserialize(&std::fs::read_to_string(path)?)?)
 }
 
 /// Saves the puzzle state to a log file.
@@ -84,6 +89,10 @@ struct LogFile {
     puzzle: Option<PuzzleTypeEnum>,
     #[serde(default)]
     state: u8,
+    /// Share code of the state that the scramble and twists are applied to,
+    /// if it is not the solved state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    initial_state: Option<String>,
     #[serde(
         default,
         skip_serializing_if = "Option::is_none",
@@ -137,6 +146,7 @@ impl LogFile {
             version: Self::VERSION,
             puzzle: Some(puzzle.ty()),
             state: puzzle.scramble_state() as u8,
+            initial_state: puzzle.initial_state().map(encode_share_code),
             visible_pieces: puzzle
                 .is_any_piece_hidden()
                 .then(|| puzzle.visible_pieces().to_bitvec()),
@@ -156,9 +166,9 @@ impl LogFile {
         }
     }
 
-    fn validate(&self) -> Result<()> {
+    fn validate(&self) -> Result<(), LoadError> {
         if let Some(puzzle_ty) = self.puzzle {
-            puzzle_ty.validate().map_err(|e| anyhow!(e))?;
+            puzzle_ty.validate().map_err(LoadError::InvalidPuzzleType)?;
         }
         Ok(())
     }
@@ -193,7 +203,7 @@ impl LogFile {
         (ret_twists, ret_errors)
     }
 
-    fn to_puzzle(&self) -> Result<(PuzzleController, Vec<String>)> {
+    fn to_puzzle(&self) -> Result<(PuzzleController, Vec<String>), LoadError> {
         self.validate()?;
 
         let mut warnings = vec![];
@@ -208,7 +218,7 @@ impl LogFile {
             ));
         }
 
-        let puzzle_type = self.puzzle.context("unable to find puzzle type")?;
+        let puzzle_type = self.puzzle.ok_or(LoadError::MissingPuzzleType)?;
         let mut ret = PuzzleController::new(puzzle_type);
 
         let scramble_state = ScrambleState::from_primitive(self.state);
@@ -217,6 +227,12 @@ impl LogFile {
             ret.set_visible_pieces(visible_pieces);
         }
 
+        if let Some(code) = &self.initial_state {
+            let snapshot = decode_share_code(code).map_err(LoadError::InvalidInitialState)?;
+            ret.display_snapshot(&snapshot)
+                .map_err(LoadError::InvalidInitialStateSnapshot)?;
+        }
+
         let (twists, parse_errors) = self.scramble();
         warnings.extend(parse_errors.iter().map(|e| e.to_string()));
         for twist in twists {
@@ -255,3 +271,101 @@ impl fmt::Display for TwistParseError<'_> {
     }
 }
 impl Error for TwistParseError<'_> {}
+
+/// Error encountered while loading a log file.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The log file is not valid YAML or does not match the expected format.
+    Yaml(serde_yaml::Error),
+    /// The log file is not a valid MC4D log file.
+    Mc4d(mc4d_compat::LogFileError),
+    /// The log file does not specify a puzzle type.
+    MissingPuzzleType,
+    /// The puzzle type in the log file is not supported.
+    InvalidPuzzleType(String),
+    /// The initial state in the log file is not a valid share code.
+    InvalidInitialState(ShareCodeError),
+    /// The initial state in the log file is not a valid state of the puzzle.
+    InvalidInitialStateSnapshot(&'static str),
+}
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Yaml(e) => write!(f, "{e}"),
+            Self::Mc4d(e) => write!(f, "invalid MC4D log file: {e}"),
+            Self::MissingPuzzleType => write!(f, "unable to find puzzle type"),
+            Self::InvalidPuzzleType(e) => write!(f, "{e}"),
+            Self::InvalidInitialState(e) => write!(f, "invalid initial state: {e}"),
+            Self::InvalidInitialStateSnapshot(e) => write!(f, "invalid initial state: {e}"),
+        }
+    }
+}
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Yaml(e) => Some(e),
+            Self::Mc4d(e) => Some(e),
+            Self::InvalidInitialState(e) => Some(e),
+            Self::MissingPuzzleType
+            | Self::InvalidPuzzleType(_)
+            | Self::InvalidInitialStateSnapshot(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_file_load_errors() {
+        assert!(matches!(
+            deserialize("version: [1"),
+            Err(LoadError::Yaml(_))
+        ));
+        assert!(matches!(
+            deserialize("version: 1\n"),
+            Err(LoadError::MissingPuzzleType),
+        ));
+        assert!(matches!(
+            deserialize("MagicCube4D"),
+            Err(LoadError::Mc4d(_)),
+        ));
+
+        let valid_log_file = LogFile::new(&PuzzleController::default()).to_string();
+        assert!(deserialize(&valid_log_file).is_ok());
+    }
+
+    #[test]
+    fn test_share_code_log_file_round_trip() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut scrambled = Puzzle::new(ty);
+        scrambled.scramble(1);
+        let code = encode_share_code(&scrambled.snapshot());
+
+        // Paste the share code and do a twist.
+        let mut puzzle =
+            PuzzleController::from_snapshot(&decode_share_code(&code).unwrap()).unwrap();
+        assert!(puzzle.is_unsaved());
+        assert_eq!(puzzle.scramble_state(), ScrambleState::Full);
+        puzzle
+            .twist(Twist {
+                axis: TwistAxis(0),
+                direction: TwistDirection(0),
+                layers: LayerMask(1),
+            })
+            .unwrap();
+
+        // Save and reload it.
+        let log_file_contents = serialize(&puzzle, LogFileFormat::Hsc).unwrap();
+        let (loaded, warnings) = deserialize(&log_file_contents).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(loaded.latest(), puzzle.latest());
+        assert_eq!(loaded.initial_state(), puzzle.initial_state());
+        assert_eq!(loaded.scramble_state(), ScrambleState::Full);
+        assert_eq!(loaded.undo_buffer(), puzzle.undo_buffer());
+
+        // MC4D log files cannot store the initial state.
+        assert!(serialize(&puzzle, LogFileFormat::Mc4d).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! Compact codes for sharing a puzzle state.
+//!
+//! A share code is URL-safe base64 encoding of the following bytes:
+//!
+//! - version (1 byte)
+//! - puzzle family (1 byte)
+//! - layer count (1 byte)
+//! - piece orientations, packed two face IDs per byte
+//! - CRC32 checksum of all previous bytes (4 bytes, little-endian)
+
+use std::error::Error;
+use std::fmt;
+
+use crate::puzzle::*;
+
+const VERSION: u8 = 1;
+const CHECKSUM_LEN: usize = 4;
+
+/// Encodes a puzzle state as a share code.
+pub fn encode_share_code(snapshot: &PuzzleSnapshot) -> String {
+    let (family, layer_count) = match snapshot.ty {
+        PuzzleTypeEnum::Rubiks3D { layer_count } => (0, layer_count),
+        PuzzleTypeEnum::Rubiks4D { layer_count } => (1, layer_count),
+    };
+    let mut bytes = vec![VERSION, family, layer_count];
+    let face_ids = snapshot.piece_orientations.concat();
+    bytes.extend(face_ids.chunks(2).map(|pair| match pair {
+        [a, b] => a << 4 | b,
+        [a] => a << 4,
+        _ => unreachable!(),
+    }));
+    bytes.extend(crc32fast::hash(&bytes).to_le_bytes());
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+/// Decodes a puzzle state from a share code.
+pub fn decode_share_code(code: &str) -> Result<PuzzleSnapshot, ShareCodeError> {
+    let bytes = base64::decode_config(code.trim(), base64::URL_SAFE_NO_PAD)
+        .map_err(|_| ShareCodeError::InvalidEncoding)?;
+    if bytes.len() < 3 + CHECKSUM_LEN {
+        return Err(ShareCodeError::TooShort);
+    }
+    let (data, checksum) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+    if crc32fast::hash(data).to_le_bytes() != checksum {
+        return Err(ShareCodeError::ChecksumMismatch);
+    }
+
+    let (header, packed_face_ids) = data.split_at(3);
+    let [version, family, layer_count] = [header[0], header[1], header[2]];
+    if version != VERSION {
+        return Err(ShareCodeError::UnsupportedVersion(version));
+    }
+    let (ty, ndim) = match family {
+        0 => (PuzzleTypeEnum::Rubiks3D { layer_count }, 3),
+        1 => (PuzzleTypeEnum::Rubiks4D { layer_count }, 4),
+        _ => return Err(ShareCodeError::InvalidPuzzleType),
+    };
+    ty.validate()
+        .map_err(|_| ShareCodeError::InvalidPuzzleType)?;
+
+    let piece_count = ty.pieces().len();
+    if packed_face_ids.len() != (piece_count * ndim + 1) / 2 {
+        return Err(ShareCodeError::WrongLength);
+    }
+    let face_ids = packed_face_ids
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0xF])
+        .take(piece_count * ndim)
+        .collect::<Vec<_>>();
+    Ok(PuzzleSnapshot {
+        ty,
+        piece_orientations: face_ids.chunks(ndim).map(|c| c.to_vec()).collect(),
+    })
+}
+
+/// Error encountered while decoding a share code.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ShareCodeError {
+    InvalidEncoding,
+    TooShort,
+    ChecksumMismatch,
+    UnsupportedVersion(u8),
+    InvalidPuzzleType,
+    WrongLength,
+}
+impl fmt::Display for ShareCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidEncoding => write!(f, "not a valid share code"),
+            Self::TooShort => write!(f, "share code is too short"),
+            Self::ChecksumMismatch => write!(f, "share code is corrupted"),
+            Self::UnsupportedVersion(v) => {
+                write!(f, "share code version {v} is not supported")
+            }
+            Self::InvalidPuzzleType => write!(f, "share code has invalid puzzle type"),
+            Self::WrongLength => write!(f, "share code has wrong length for puzzle"),
+        }
+    }
+}
+impl Error for ShareCodeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrambled_snapshot(ty: PuzzleTypeEnum) -> PuzzleSnapshot {
+        let mut puzzle = Puzzle::new(ty);
+        let twists: Vec<_> = puzzle.legal_twists().step_by(5).collect();
+        for twist in twists {
+            puzzle.twist(twist).unwrap();
+        }
+        puzzle.snapshot()
+    }
+
+    #[test]
+    fn test_share_code_round_trip() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 2 },
+            PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 3 },
+        ] {
+            let snapshot = scrambled_snapshot(ty);
+            let code = encode_share_code(&snapshot);
+            assert_eq!(decode_share_code(&code), Ok(snapshot));
+        }
+    }
+
+    #[test]
+    fn test_share_code_corruption() {
+        let snapshot = scrambled_snapshot(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        let code = encode_share_code(&snapshot);
+
+        // Change one character in the middle.
+        let mut corrupted = code.clone().into_bytes();
+        let i = corrupted.len() / 2;
+        corrupted[i] = if corrupted[i] == b'A' { b'B' } else { b'A' };
+        let corrupted = String::from_utf8(corrupted).unwrap();
+        assert_eq!(
+            decode_share_code(&corrupted),
+            Err(ShareCodeError::ChecksumMismatch),
+        );
+
+        assert_eq!(
+            decode_share_code("not a share code!"),
+            Err(ShareCodeError::InvalidEncoding),
+        );
+        assert_eq!(decode_share_code(&code[..4]), Err(ShareCodeError::TooShort));
+
+        // Re-encode with a different version byte.
+        let mut bytes = base64::decode_config(&code, base64::URL_SAFE_NO_PAD).unwrap();
+        bytes[0] = 99;
+        let data_len = bytes.len() - CHECKSUM_LEN;
+        let checksum = crc32fast::hash(&bytes[..data_len]).to_le_bytes();
+        bytes[data_len..].copy_from_slice(&checksum);
+        let future_code = base64::encode_config(bytes, base64::URL_SAFE_NO_PAD);
+        assert_eq!(
+            decode_share_code(&future_code),
+            Err(ShareCodeError::UnsupportedVersion(99)),
+        );
+    }
+}
@@ -38,7 +38,7 @@ mod icon;
 mod logfile;
 mod preferences;
 pub mod puzzle;
-mod render;
+pub mod render;
 mod serde_impl;
 mod util;
 #[cfg(target_arch = "wasm32")]
@@ -385,7 +385,7 @@ async fn run() {
                     // Update app state.
                     app.frame();
 
-                    let output_frame = match gfx.surface.get_current_texture() {
+                    let output_frame = match gfx.surface().get_current_texture() {
                         Ok(tex) => tex,
                         // Log other errors to the console.
                         Err(e) => {
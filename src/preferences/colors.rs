@@ -2,8 +2,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::ops::{Index, IndexMut};
 
-use super::PerPuzzleFamily;
-use crate::puzzle::{traits::*, Face, PuzzleTypeEnum};
+use super::{PerPuzzle, PerPuzzleFamily};
+use crate::puzzle::{traits::*, Face, PuzzleTypeEnum, Sticker};
 use crate::serde_impl::hex_color;
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -16,6 +16,9 @@ pub struct ColorPreferences {
     pub blindfold: bool,
 
     pub faces: PerPuzzleFamily<BTreeMap<String, FaceColor>>,
+    /// Custom colors for individual stickers, which take precedence over the
+    /// face colors.
+    pub sticker_overrides: PerPuzzle<Vec<StickerColorOverride>>,
 }
 impl Index<(PuzzleTypeEnum, Face)> for ColorPreferences {
     type Output = egui::Color32;
@@ -40,6 +43,14 @@ impl IndexMut<(PuzzleTypeEnum, Face)> for ColorPreferences {
     }
 }
 
+/// Custom color for a single sticker.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StickerColorOverride {
+    pub sticker: u16,
+    #[serde(with = "hex_color")]
+    pub color: egui::Color32,
+}
+
 // TODO: rename this type and use it for all colors. also impl display
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(transparent)]
@@ -56,4 +67,52 @@ impl ColorPreferences {
             })
             .collect()
     }
+
+    /// Sets or clears the custom color of a sticker.
+    pub fn set_sticker_color(
+        &mut self,
+        ty: PuzzleTypeEnum,
+        sticker: Sticker,
+        color: Option<egui::Color32>,
+    ) {
+        let overrides = &mut self.sticker_overrides[ty];
+        overrides.retain(|o| o.sticker != sticker.0);
+        if let Some(color) = color {
+            overrides.push(StickerColorOverride {
+                sticker: sticker.0,
+                color,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sticker_color_overrides() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let other_ty = PuzzleTypeEnum::Rubiks3D { layer_count: 4 };
+        let mut prefs = ColorPreferences::default();
+
+        prefs.set_sticker_color(ty, Sticker(5), Some(egui::Color32::RED));
+        prefs.set_sticker_color(ty, Sticker(7), Some(egui::Color32::GREEN));
+        prefs.set_sticker_color(ty, Sticker(5), Some(egui::Color32::BLUE));
+        let sticker_color = |prefs: &ColorPreferences, ty, sticker: u16| {
+            prefs.sticker_overrides[ty]
+                .iter()
+                .find(|o| o.sticker == sticker)
+                .map(|o| o.color)
+        };
+        assert_eq!(prefs.sticker_overrides[ty].len(), 2);
+        assert_eq!(sticker_color(&prefs, ty, 5), Some(egui::Color32::BLUE));
+        assert_eq!(sticker_color(&prefs, ty, 7), Some(egui::Color32::GREEN));
+
+        // Overrides are stored per puzzle.
+        assert!(prefs.sticker_overrides[other_ty].is_empty());
+
+        prefs.set_sticker_color(ty, Sticker(7), None);
+        assert_eq!(sticker_color(&prefs, ty, 7), None);
+    }
 }
@@ -6,12 +6,34 @@ use serde::{Deserialize, Serialize};
 pub struct GfxPreferences {
     pub fps_limit: usize,
     pub msaa: bool,
+    /// Factor by which to scale the resolution of the puzzle texture. Values
+    /// greater than 1.0 render at a higher resolution and then downsample.
+    pub render_scale: f32,
+
+    /// Whether and how to render a stereo pair of images.
+    pub stereo_mode: StereoMode,
+    /// Distance between the eyes for stereo rendering, as a fraction of the
+    /// puzzle radius.
+    pub stereo_eye_separation: f32,
+
+    /// Factor by which to scale the resolution of screenshots, relative to
+    /// the size of the puzzle view.
+    pub screenshot_scale: f32,
+    /// Whether to leave the background of screenshots transparent.
+    pub screenshot_transparent: bool,
 }
 impl Default for GfxPreferences {
     fn default() -> Self {
         Self {
             fps_limit: 60,
             msaa: true,
+            render_scale: 1.0,
+
+            stereo_mode: StereoMode::Off,
+            stereo_eye_separation: 0.1,
+
+            screenshot_scale: 2.0,
+            screenshot_transparent: false,
         }
     }
 }
@@ -29,4 +51,91 @@ impl GfxPreferences {
             1
         }
     }
+
+    /// Returns the size of the texture to render the puzzle to, given the size
+    /// of the target rectangle and the maximum texture dimension supported by
+    /// the device. The aspect ratio is preserved.
+    pub fn scaled_texture_size(&self, size: (u32, u32), max_dimension: u32) -> (u32, u32) {
+        scale_texture_size(self.render_scale, size, max_dimension)
+    }
+
+    /// Returns the size of a screenshot, given the size of the target
+    /// rectangle and the maximum texture dimension supported by the device.
+    /// The aspect ratio is preserved.
+    pub fn screenshot_size(&self, size: (u32, u32), max_dimension: u32) -> (u32, u32) {
+        scale_texture_size(self.screenshot_scale, size, max_dimension)
+    }
+}
+
+/// Method for rendering a stereo pair of images.
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Display,
+    IntoStaticStr,
+    EnumIter,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum StereoMode {
+    /// Render a single image.
+    #[default]
+    #[strum(serialize = "Off")]
+    Off,
+    /// Render the left and right eyes next to each other.
+    #[strum(serialize = "Side by side")]
+    SideBySide,
+    /// Render the left eye in red and the right eye in cyan.
+    #[strum(serialize = "Anaglyph")]
+    Anaglyph,
+}
+
+fn scale_texture_size(scale: f32, (w, h): (u32, u32), max_dimension: u32) -> (u32, u32) {
+    let mut scale = if scale.is_finite() && scale > 0.0 {
+        scale
+    } else {
+        1.0
+    };
+    let max_side = std::cmp::max(w, h) as f32;
+    if max_side * scale > max_dimension as f32 {
+        scale = max_dimension as f32 / max_side;
+    }
+    let scale_dimension = |x: u32| ((x as f32 * scale).round() as u32).clamp(1, max_dimension);
+    (scale_dimension(w), scale_dimension(h))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scaled_texture_size() {
+        let mut prefs = GfxPreferences::default();
+        assert_eq!(prefs.scaled_texture_size((800, 600), 8192), (800, 600));
+
+        prefs.render_scale = 2.0;
+        assert_eq!(prefs.scaled_texture_size((800, 600), 8192), (1600, 1200));
+
+        // Respect device limits, preserving aspect ratio.
+        assert_eq!(prefs.scaled_texture_size((4000, 2000), 4096), (4096, 2048));
+        assert_eq!(prefs.scaled_texture_size((8000, 2000), 4096), (4096, 1024));
+
+        // Invalid scale factors are ignored.
+        prefs.render_scale = f32::NAN;
+        assert_eq!(prefs.scaled_texture_size((800, 600), 8192), (800, 600));
+    }
+
+    #[test]
+    fn test_screenshot_size() {
+        let mut prefs = GfxPreferences::default();
+        prefs.screenshot_scale = 3.0;
+        assert_eq!(prefs.screenshot_size((800, 600), 8192), (2400, 1800));
+        assert_eq!(prefs.screenshot_size((800, 600), 1200), (1200, 900));
+    }
 }
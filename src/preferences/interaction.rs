@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default)]
@@ -9,8 +10,81 @@ pub struct InteractionPreferences {
     pub realign_on_release: bool,
     pub realign_on_keypress: bool,
     pub smart_realign: bool,
+    /// Maximum angle, in degrees, between the view and the nearest similar
+    /// orientation for smart realign to snap to it instead of the original.
+    pub smart_realign_threshold: f32,
 
     pub dynamic_twist_speed: bool,
+    /// Whether twists, undos, and redos from user input are rejected while a
+    /// twist from user input is animating, instead of being queued.
+    pub blocking_twist_animations: bool,
     pub twist_duration: f32,
     pub other_anim_duration: f32,
+
+    /// Whether to show a translucent preview of the result of the twist that
+    /// would be performed by clicking on the hovered sticker.
+    pub preview_hovered_twist: bool,
+    /// Whether to show a tooltip listing the faces adjacent to the face of
+    /// the hovered sticker.
+    pub show_adjacent_faces: bool,
+
+    /// Per-axis overrides, keyed by twist axis name (such as `R` or `O`).
+    pub axis_overrides: HashMap<String, AxisInteractionPreferences>,
+}
+impl InteractionPreferences {
+    /// Returns the twist duration to use for a twist on the given axis,
+    /// falling back to the global twist duration.
+    pub fn twist_duration_for_axis(&self, axis_name: &str) -> f32 {
+        self.axis_overrides
+            .get(axis_name)
+            .and_then(|o| o.twist_duration)
+            .unwrap_or(self.twist_duration)
+    }
+    /// Returns whether twist commands on the given axis should be reversed.
+    pub fn is_axis_reversed(&self, axis_name: &str) -> bool {
+        self.axis_overrides
+            .get(axis_name)
+            .map_or(false, |o| o.reverse_direction)
+    }
+}
+
+/// Interaction preferences for a single twist axis.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct AxisInteractionPreferences {
+    /// Twist duration for this axis, or `None` to use the global value.
+    pub twist_duration: Option<f32>,
+    /// Whether to reverse the direction of twist commands on this axis.
+    pub reverse_direction: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_axis_twist_duration_override() {
+        let mut prefs = InteractionPreferences {
+            twist_duration: 0.2,
+            ..Default::default()
+        };
+        prefs.axis_overrides.insert(
+            "R".to_string(),
+            AxisInteractionPreferences {
+                twist_duration: Some(0.5),
+                reverse_direction: true,
+            },
+        );
+        prefs
+            .axis_overrides
+            .insert("U".to_string(), AxisInteractionPreferences::default());
+
+        assert_eq!(prefs.twist_duration_for_axis("R"), 0.5);
+        assert_eq!(prefs.twist_duration_for_axis("U"), 0.2);
+        assert_eq!(prefs.twist_duration_for_axis("F"), 0.2);
+
+        assert!(prefs.is_axis_reversed("R"));
+        assert!(!prefs.is_axis_reversed("U"));
+        assert!(!prefs.is_axis_reversed("F"));
+    }
 }
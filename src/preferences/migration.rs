@@ -18,6 +18,12 @@ pub(super) fn try_deserialize(c: Config) -> Result<Preferences, ConfigError> {
         );
         persist::backup_prefs_file();
     }
+    deserialize_version(c, version)
+}
+
+/// Deserializes preferences stored in the format of a particular version,
+/// migrating them to the latest version.
+fn deserialize_version(c: Config, version: u32) -> Result<Preferences, ConfigError> {
     Ok(match version {
         0 => c.try_deserialize::<v0::PrefsCompat>()?.into(),
         1 => c.try_deserialize::<v1::PrefsCompat>()?,
@@ -178,3 +184,56 @@ impl<T: Default + Clone> From<v0::WithPresets<T>> for WithPresets<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_from_yaml(s: &str) -> Config {
+        Config::builder()
+            .add_source(config::File::from_str(s, PREFS_FILE_FORMAT))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_migrate_prefs_from_v0() {
+        let c = config_from_yaml(
+            r#"
+view_3d:
+  pitch: 10.0
+  active_preset: tilted
+  presets:
+    tilted:
+      pitch: 20.0
+piece_filters:
+  3x3x3:
+    corners: "ff"
+"#,
+        );
+        let prefs = deserialize_version(c, 0).unwrap();
+
+        // View presets used to be a map; now they are a list.
+        assert_eq!(prefs.view_3d.current.pitch, 10.0);
+        assert_eq!(prefs.view_3d.presets.len(), 1);
+        let preset = &prefs.view_3d.presets[0];
+        assert_eq!(preset.preset_name, "tilted");
+        assert_eq!(preset.value.pitch, 20.0);
+        assert_eq!(prefs.view_3d.active_preset.as_ref(), Some(preset));
+
+        // Piece filters used to be strings; now they are presets.
+        let filters = &prefs.piece_filters.map["3x3x3"];
+        assert_eq!(filters.len(), 1);
+        assert_eq!(filters[0].preset_name, "corners");
+        assert_eq!(filters[0].value.hidden_opacity, None);
+    }
+
+    #[test]
+    fn test_deserialize_latest_prefs() {
+        let c = config_from_yaml(&format!(
+            "version: {LATEST_VERSION}\ninteraction:\n  twist_duration: 0.5\n",
+        ));
+        let prefs = try_deserialize(c).unwrap();
+        assert_eq!(prefs.interaction.twist_duration, 0.5);
+    }
+}
@@ -237,7 +237,7 @@ impl<T: Default> Default for Preset<T> {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 #[serde(transparent)]
 pub struct PerPuzzle<T> {
     map: BTreeMap<String, T>,
@@ -263,6 +263,9 @@ impl<T> PerPuzzle<T> {
     fn get(&self, puzzle_type: PuzzleTypeEnum) -> Option<&T> {
         self.map.get(puzzle_type.name())
     }
+    fn remove(&mut self, puzzle_type: PuzzleTypeEnum) -> Option<T> {
+        self.map.remove(puzzle_type.name())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
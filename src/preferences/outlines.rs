@@ -19,4 +19,85 @@ pub struct OutlinePreferences {
     pub selected_sticker_color: egui::Color32,
     #[serde(with = "hex_color")]
     pub selected_piece_color: egui::Color32,
+
+    /// How to compute the default outline color.
+    pub default_color_mode: OutlineColorMode,
+    /// How much to darken or lighten the face color, from 0.0 to 1.0, when
+    /// `default_color_mode` is not `Fixed`.
+    pub face_color_blend: f32,
+}
+impl OutlinePreferences {
+    /// Returns the default outline color for a sticker with the given face
+    /// color.
+    pub fn default_color_for_face(&self, face_color: egui::Color32) -> egui::Rgba {
+        let face_color = egui::Rgba::from(face_color);
+        match self.default_color_mode {
+            OutlineColorMode::Fixed => egui::Rgba::from(self.default_color),
+            OutlineColorMode::Darken => {
+                crate::util::mix(face_color, egui::Rgba::BLACK, self.face_color_blend)
+            }
+            OutlineColorMode::Lighten => {
+                crate::util::mix(face_color, egui::Rgba::WHITE, self.face_color_blend)
+            }
+        }
+    }
+}
+
+/// Method for computing the default outline color of a sticker.
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Display,
+    IntoStaticStr,
+    EnumIter,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum OutlineColorMode {
+    /// Use a fixed color for all stickers.
+    #[default]
+    #[strum(serialize = "Fixed")]
+    Fixed,
+    /// Darken the face color of the sticker.
+    #[strum(serialize = "Darken face")]
+    Darken,
+    /// Lighten the face color of the sticker.
+    #[strum(serialize = "Lighten face")]
+    Lighten,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outline_color_from_face_color() {
+        let face_color = egui::Color32::from_rgb(200, 100, 50);
+        let mut prefs = OutlinePreferences {
+            default_color: egui::Color32::from_rgb(1, 2, 3),
+            face_color_blend: 0.5,
+            ..Default::default()
+        };
+
+        prefs.default_color_mode = OutlineColorMode::Fixed;
+        assert_eq!(
+            prefs.default_color_for_face(face_color),
+            egui::Rgba::from(prefs.default_color),
+        );
+
+        prefs.default_color_mode = OutlineColorMode::Darken;
+        let darkened = prefs.default_color_for_face(face_color);
+        assert_eq!(darkened, egui::Rgba::from(face_color) * 0.5);
+
+        prefs.default_color_mode = OutlineColorMode::Lighten;
+        let lightened = prefs.default_color_for_face(face_color);
+        let expected = egui::Rgba::from(face_color) * 0.5 + egui::Rgba::WHITE * 0.5;
+        assert_eq!(lightened, expected);
+    }
 }
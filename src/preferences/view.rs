@@ -1,6 +1,9 @@
-use cgmath::{Deg, Quaternion, Rotation3};
+use cgmath::{Deg, Matrix3, Quaternion, Rad, Rotation3};
 use serde::{Deserialize, Serialize};
 
+use super::PerPuzzle;
+use crate::puzzle::PuzzleTypeEnum;
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(default)]
 pub struct ViewPreferences {
@@ -15,8 +18,13 @@ pub struct ViewPreferences {
     pub scale: f32,
     /// 3D FOV, in degrees (may be negative).
     pub fov_3d: f32,
+    /// Whether to use an orthographic 3D projection, ignoring the 3D FOV.
+    pub orthographic_3d: bool,
     /// 4D FOV, in degrees.
     pub fov_4d: f32,
+    /// Offset of the 4D camera along the X, Y, and Z axes, as a fraction of
+    /// the distance from the camera to the puzzle center.
+    pub camera_4d_offset: [f32; 3],
 
     /// Horizontal alignment, from -1.0 to +1.0.
     pub align_h: f32,
@@ -25,6 +33,9 @@ pub struct ViewPreferences {
 
     pub show_frontfaces: bool,
     pub show_backfaces: bool,
+    /// Face visibility for specific puzzles, overriding `show_frontfaces` and
+    /// `show_backfaces`.
+    pub puzzle_face_visibility: PerPuzzle<FaceVisibility>,
     pub clip_4d: bool,
 
     pub face_spacing: f32,
@@ -36,6 +47,9 @@ pub struct ViewPreferences {
     pub light_directional: f32,
     pub light_pitch: f32,
     pub light_yaw: f32,
+    pub fill_light: f32,
+    pub fill_light_pitch: f32,
+    pub fill_light_yaw: f32,
 }
 impl Default for ViewPreferences {
     fn default() -> Self {
@@ -46,7 +60,9 @@ impl Default for ViewPreferences {
 
             scale: 1.0,
             fov_3d: 30_f32,
+            orthographic_3d: false,
             fov_4d: 30_f32,
+            camera_4d_offset: [0.0; 3],
 
             align_h: 0.0,
             align_v: 0.0,
@@ -56,6 +72,7 @@ impl Default for ViewPreferences {
 
             show_frontfaces: true,
             show_backfaces: true,
+            puzzle_face_visibility: PerPuzzle::default(),
             clip_4d: true,
 
             outline_thickness: 1.0,
@@ -64,6 +81,9 @@ impl Default for ViewPreferences {
             light_directional: 0.0,
             light_pitch: 0.0,
             light_yaw: 0.0,
+            fill_light: 0.0,
+            fill_light_pitch: 0.0,
+            fill_light_yaw: 0.0,
         }
     }
 }
@@ -75,19 +95,80 @@ impl ViewPreferences {
             * Quaternion::from_angle_y(Deg(self.yaw))
     }
 
+    /// Rolls the view around the axis pointing toward the camera, keeping the
+    /// roll between -180 and +180 degrees.
+    pub fn add_roll(&mut self, roll: f32) {
+        self.roll = (self.roll + roll + 180.0).rem_euclid(360.0) - 180.0;
+    }
+
+    /// Returns which faces to show for a puzzle.
+    pub fn face_visibility(&self, puzzle_type: PuzzleTypeEnum) -> FaceVisibility {
+        self.puzzle_face_visibility
+            .get(puzzle_type)
+            .copied()
+            .unwrap_or(FaceVisibility {
+                show_frontfaces: self.show_frontfaces,
+                show_backfaces: self.show_backfaces,
+            })
+    }
+    /// Returns whether face visibility is set specifically for a puzzle.
+    pub fn has_puzzle_face_visibility(&self, puzzle_type: PuzzleTypeEnum) -> bool {
+        self.puzzle_face_visibility.get(puzzle_type).is_some()
+    }
+    /// Sets face visibility specifically for a puzzle, or `None` to use the
+    /// global face visibility.
+    pub fn set_puzzle_face_visibility(
+        &mut self,
+        puzzle_type: PuzzleTypeEnum,
+        face_visibility: Option<FaceVisibility>,
+    ) {
+        match face_visibility {
+            Some(v) => self.puzzle_face_visibility[puzzle_type] = v,
+            None => {
+                self.puzzle_face_visibility.remove(puzzle_type);
+            }
+        }
+    }
+
+    /// Returns the pitch, yaw, and roll (in degrees) of a view angle, such
+    /// that `view_angle()` would return the same rotation.
+    fn euler_angles(view_angle: Quaternion<f32>) -> [f32; 3] {
+        // `view_angle()` is `Rz(roll) * Rx(pitch) * Ry(yaw)`. Note that cgmath
+        // matrices are column-major.
+        let m = Matrix3::from(view_angle);
+        let pitch = Rad(m.y.z.clamp(-1.0, 1.0).asin());
+        let (yaw, roll) = if pitch.0.cos() > 0.0001 {
+            (
+                Rad(f32::atan2(-m.x.z, m.z.z)),
+                Rad(f32::atan2(-m.y.x, m.y.y)),
+            )
+        } else {
+            // Gimbal lock: yaw and roll rotate around the same axis.
+            (Rad(0.0), Rad(f32::atan2(m.x.y, m.x.x)))
+        };
+        [pitch, yaw, roll].map(|angle| Deg::from(angle).0)
+    }
+
     // TODO: make a proc macro crate to generate a trait impl like this
     pub fn interpolate(&self, rhs: &Self, t: f32) -> Self {
+        // Interpolate orientation along the shortest path.
+        let [pitch, yaw, roll] = Self::euler_angles(self.view_angle().slerp(rhs.view_angle(), t));
+
         Self {
-            // I know, I know, I should use quaternions for interpolation. But
-            // cgmath uses XYZ order by default instead of YXZ so doing this
-            // properly isn't trivial.
-            pitch: crate::util::mix(self.pitch, rhs.pitch, t),
-            yaw: crate::util::mix(self.yaw, rhs.yaw, t),
-            roll: crate::util::mix(self.roll, rhs.roll, t),
+            pitch,
+            yaw,
+            roll,
 
             scale: crate::util::mix(self.scale, rhs.scale, t),
             fov_3d: crate::util::mix(self.fov_3d, rhs.fov_3d, t),
+            orthographic_3d: if t < 0.5 {
+                self.orthographic_3d
+            } else {
+                rhs.orthographic_3d
+            },
             fov_4d: crate::util::mix(self.fov_4d, rhs.fov_4d, t),
+            camera_4d_offset: [0, 1, 2]
+                .map(|i| crate::util::mix(self.camera_4d_offset[i], rhs.camera_4d_offset[i], t)),
             align_h: crate::util::mix(self.align_h, rhs.align_h, t),
             align_v: crate::util::mix(self.align_v, rhs.align_v, t),
             show_frontfaces: if t < 0.5 {
@@ -100,6 +181,11 @@ impl ViewPreferences {
             } else {
                 rhs.show_backfaces
             },
+            puzzle_face_visibility: if t < 0.5 {
+                self.puzzle_face_visibility.clone()
+            } else {
+                rhs.puzzle_face_visibility.clone()
+            },
             clip_4d: if t < 0.5 { self.clip_4d } else { rhs.clip_4d },
             face_spacing: crate::util::mix(self.face_spacing, rhs.face_spacing, t),
             sticker_spacing: crate::util::mix(self.sticker_spacing, rhs.sticker_spacing, t),
@@ -108,6 +194,133 @@ impl ViewPreferences {
             light_directional: crate::util::mix(self.light_directional, rhs.light_directional, t),
             light_pitch: crate::util::mix(self.light_pitch, rhs.light_pitch, t),
             light_yaw: crate::util::mix(self.light_yaw, rhs.light_yaw, t),
+            fill_light: crate::util::mix(self.fill_light, rhs.fill_light, t),
+            fill_light_pitch: crate::util::mix(self.fill_light_pitch, rhs.fill_light_pitch, t),
+            fill_light_yaw: crate::util::mix(self.fill_light_yaw, rhs.fill_light_yaw, t),
         }
     }
 }
+
+/// Whether to show the front and back faces of stickers.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct FaceVisibility {
+    pub show_frontfaces: bool,
+    pub show_backfaces: bool,
+}
+impl Default for FaceVisibility {
+    fn default() -> Self {
+        Self {
+            show_frontfaces: true,
+            show_backfaces: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::InnerSpace;
+
+    use super::*;
+
+    fn assert_same_rotation(a: Quaternion<f32>, b: Quaternion<f32>) {
+        // `q` and `-q` represent the same rotation.
+        assert!(a.dot(b).abs() > 0.9999, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_euler_angles_round_trip() {
+        for [pitch, yaw, roll] in [
+            [0.0, 0.0, 0.0],
+            [35.0, -20.0, 0.0],
+            [-60.0, 150.0, 30.0],
+            [90.0, 0.0, 45.0],
+        ] {
+            let prefs = ViewPreferences {
+                pitch,
+                yaw,
+                roll,
+                ..Default::default()
+            };
+            let [pitch, yaw, roll] = ViewPreferences::euler_angles(prefs.view_angle());
+            let round_tripped = ViewPreferences {
+                pitch,
+                yaw,
+                roll,
+                ..Default::default()
+            };
+            assert_same_rotation(prefs.view_angle(), round_tripped.view_angle());
+        }
+    }
+
+    #[test]
+    fn test_view_interpolation() {
+        let a = ViewPreferences {
+            pitch: 0.0,
+            yaw: 0.0,
+            roll: 0.0,
+            scale: 1.0,
+            ..Default::default()
+        };
+        let b = ViewPreferences {
+            yaw: 90.0,
+            scale: 2.0,
+            ..a.clone()
+        };
+        let mid = a.interpolate(&b, 0.5);
+        assert!((mid.yaw - 45.0).abs() < 0.001);
+        assert!(mid.pitch.abs() < 0.001);
+        assert!(mid.roll.abs() < 0.001);
+        assert!((mid.scale - 1.5).abs() < 0.001);
+
+        // Orientation takes the shortest path, even across the -180/+180
+        // boundary.
+        let a = ViewPreferences { yaw: 170.0, ..a };
+        let b = ViewPreferences { yaw: -170.0, ..b };
+        let mid = a.interpolate(&b, 0.5);
+        assert_same_rotation(mid.view_angle(), Quaternion::from_angle_y(Deg(180.0)));
+        assert_same_rotation(a.interpolate(&b, 0.0).view_angle(), a.view_angle());
+        assert_same_rotation(a.interpolate(&b, 1.0).view_angle(), b.view_angle());
+    }
+
+    #[test]
+    fn test_add_roll() {
+        let mut prefs = ViewPreferences::default();
+        prefs.add_roll(170.0);
+        assert_eq!(prefs.roll, 170.0);
+        prefs.add_roll(30.0);
+        assert_eq!(prefs.roll, -160.0);
+        prefs.add_roll(-40.0);
+        assert_eq!(prefs.roll, 160.0);
+    }
+
+    #[test]
+    fn test_puzzle_face_visibility() {
+        let cube = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let other = PuzzleTypeEnum::Rubiks3D { layer_count: 2 };
+        let global = FaceVisibility {
+            show_frontfaces: true,
+            show_backfaces: false,
+        };
+        let specific = FaceVisibility {
+            show_frontfaces: false,
+            show_backfaces: true,
+        };
+
+        let mut prefs = ViewPreferences {
+            show_frontfaces: true,
+            show_backfaces: false,
+            ..Default::default()
+        };
+        assert_eq!(prefs.face_visibility(cube), global);
+
+        prefs.set_puzzle_face_visibility(cube, Some(specific));
+        assert!(prefs.has_puzzle_face_visibility(cube));
+        assert_eq!(prefs.face_visibility(cube), specific);
+        assert_eq!(prefs.face_visibility(other), global);
+
+        prefs.set_puzzle_face_visibility(cube, None);
+        assert!(!prefs.has_puzzle_face_visibility(cube));
+        assert_eq!(prefs.face_visibility(cube), global);
+    }
+}
@@ -1,7 +1,8 @@
 use cgmath::{One, Quaternion, Rotation};
 use enum_iterator::Sequence;
 use itertools::Itertools;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
@@ -45,12 +46,96 @@ pub trait PuzzleType {
             .map(TwistDirection)
             .find(|&twist_direction| self.info(twist_direction).name == name)
     }
+    /// Returns the list of adjacent faces for each face, indexed by face. Two
+    /// faces are adjacent if some piece has stickers of both colors.
+    fn face_adjacency(&self) -> Vec<Vec<Face>> {
+        let mut ret = vec![vec![]; self.faces().len()];
+        for piece in self.pieces() {
+            let colors = piece.stickers.iter().map(|&s| self.info(s).color);
+            for (a, b) in colors.tuple_combinations() {
+                if a != b && !ret[a.0 as usize].contains(&b) {
+                    ret[a.0 as usize].push(b);
+                    ret[b.0 as usize].push(a);
+                }
+            }
+        }
+        for adjacent_faces in &mut ret {
+            adjacent_faces.sort_by_key(|face| face.0);
+        }
+        ret
+    }
+    /// Checks that every sticker belongs to exactly one piece, and that each
+    /// sticker's piece agrees with the piece that lists it.
+    fn check_sticker_pieces(&self) -> Result<(), String> {
+        let mut owners: Vec<Option<Piece>> = vec![None; self.stickers().len()];
+        for (i, piece_info) in self.pieces().iter().enumerate() {
+            let piece = Piece(i as _);
+            for &sticker in &piece_info.stickers {
+                let owner = owners
+                    .get_mut(sticker.0 as usize)
+                    .ok_or_else(|| format!("{piece:?} has nonexistent {sticker:?}"))?;
+                if let Some(other) = owner.replace(piece) {
+                    return Err(format!("{sticker:?} is on both {other:?} and {piece:?}"));
+                }
+            }
+        }
+        for (i, (sticker_info, owner)) in self.stickers().iter().zip(owners).enumerate() {
+            let sticker = Sticker(i as _);
+            match owner {
+                None => return Err(format!("{sticker:?} is not on any piece")),
+                Some(piece) if piece != sticker_info.piece => {
+                    return Err(format!(
+                        "{sticker:?} is on {piece:?} but claims to be on {:?}",
+                        sticker_info.piece,
+                    ));
+                }
+                Some(_) => (),
+            }
+        }
+        Ok(())
+    }
+    /// Returns the number of times a twist direction must be repeated to
+    /// return to the initial state.
+    fn twist_direction_order(&self, direction: TwistDirection) -> usize {
+        // The group of directions has at most one more element than the
+        // number of directions, so this always terminates.
+        (1..=self.twist_directions().len() + 1)
+            .find(|&n| self.chain_twist_directions(&vec![direction; n]).is_none())
+            .expect("twist direction has no finite order")
+    }
+    /// Returns the order of a twist axis, which is the largest order of any
+    /// single twist direction around it.
+    fn twist_axis_order(&self, _axis: TwistAxis) -> usize {
+        // Every axis supports the same set of twist directions.
+        (0..self.twist_directions().len() as u8)
+            .map(|i| self.twist_direction_order(TwistDirection(i)))
+            .max()
+            .unwrap_or(1)
+    }
+
     fn opposite_twist_axis(&self, twist_axis: TwistAxis) -> Option<TwistAxis>;
     fn count_quarter_turns(&self, twist: Twist) -> usize;
 
+    /// Returns the unit vector perpendicular to the outermost layer of a twist
+    /// axis, pointing outward.
+    fn twist_axis_normal(&self, twist_axis: TwistAxis) -> Vec<f32>;
+    /// Returns the distance of each cut along a twist axis from the center of
+    /// the puzzle, from outermost to innermost, where the outermost face is at
+    /// distance 1.
+    fn twist_axis_cuts(&self, _twist_axis: TwistAxis) -> Vec<f32> {
+        // Every layer has the same thickness.
+        let layer_count = self.layer_count();
+        (1..layer_count)
+            .map(|i| 1.0 - 2.0 * i as f32 / layer_count as f32)
+            .collect()
+    }
+    /// Returns the matrix, as a list of rows, that transforms the pieces
+    /// affected by a twist.
+    fn twist_transform(&self, twist_axis: TwistAxis, direction: TwistDirection) -> Vec<Vec<f32>>;
+
     fn check_layers(&self, layers: LayerMask) -> Result<(), &'static str> {
         let layer_count = self.layer_count() as u32;
-        if layers.0 > 0 || layers.0 < 1 << layer_count {
+        if layers.0 > 0 && layers.0 < 1 << layer_count {
             Ok(())
         } else {
             Err("invalid layer mask")
@@ -136,6 +221,8 @@ impl<'a, P: PuzzleType> PuzzleTypeRefExt for &'a P {
 #[enum_dispatch]
 pub trait PuzzleState: PuzzleType {
     fn twist(&mut self, twist: Twist) -> Result<(), &'static str>;
+    /// Restores every piece to its solved position and orientation.
+    fn reset(&mut self);
     fn is_piece_affected_by_twist(&self, twist: Twist, piece: Piece) -> bool {
         twist.layers[self.layer_from_twist_axis(twist.axis, piece)]
     }
@@ -179,10 +266,84 @@ pub trait PuzzleState: PuzzleType {
 
     fn is_solved(&self) -> bool;
 
+    /// Returns an error if the twist cannot be applied to the puzzle in its
+    /// current state.
+    fn can_twist(&self, twist: Twist) -> Result<(), &'static str> {
+        if twist.axis.0 as usize >= self.twist_axes().len() {
+            return Err("invalid twist axis");
+        }
+        if twist.direction.0 as usize >= self.twist_directions().len() {
+            return Err("invalid twist direction");
+        }
+        self.check_layers(twist.layers)
+    }
+    /// Returns every twist that can be applied to the puzzle in its current
+    /// state.
+    fn legal_twists(&self) -> Box<dyn Iterator<Item = Twist> + '_> {
+        Box::new(
+            itertools::iproduct!(
+                (0..self.twist_axes().len() as _).map(TwistAxis),
+                (0..self.twist_directions().len() as _).map(TwistDirection),
+                (1..1_u32 << self.layer_count()).map(LayerMask)
+            )
+            .map(|(axis, direction, layers)| Twist {
+                axis,
+                direction,
+                layers,
+            })
+            .filter(|&twist| self.can_twist(twist).is_ok()),
+        )
+    }
+    /// Applies a random scramble determined entirely by `seed` and returns the
+    /// canonicalized twists that were applied. No twist immediately undoes the
+    /// previous one.
+    fn scramble(&mut self, seed: u64) -> Vec<Twist> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut twists: Vec<Twist> = vec![];
+        while twists.len() < self.scramble_moves_count() {
+            let twist = self.canonicalize_twist(Twist::random(self.ty(), &mut rng));
+            let undoes_previous = twists.last().map_or(false, |&prev| {
+                self.canonicalize_twist(self.reverse_twist(twist)) == prev
+            });
+            if !undoes_previous && self.twist(twist).is_ok() {
+                twists.push(twist);
+            }
+        }
+        twists
+    }
+
+    /// Returns the orientation of every piece in a serializable form.
+    fn snapshot(&self) -> PuzzleSnapshot;
+    /// Sets the orientation of every piece from a snapshot. If the snapshot is
+    /// invalid, the puzzle is left unchanged.
+    fn load_snapshot(&mut self, snapshot: &PuzzleSnapshot) -> Result<(), &'static str>;
+
     #[cfg(debug_assertions)]
     fn sticker_debug_info(&self, _s: &mut String, _sticker: Sticker) {}
 }
 
+/// Serializable state of every piece in a puzzle, which can be used to drive
+/// the renderer without replaying twists.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PuzzleSnapshot {
+    /// Puzzle type that the snapshot was taken from.
+    pub ty: PuzzleTypeEnum,
+    /// For each piece, the ID of the face that each of its axes points
+    /// toward.
+    pub piece_orientations: Vec<Vec<u8>>,
+}
+impl PuzzleSnapshot {
+    /// Returns the pieces whose orientation differs between two snapshots of
+    /// the same puzzle type.
+    pub fn diff(&self, other: &PuzzleSnapshot) -> Vec<Piece> {
+        std::iter::zip(&self.piece_orientations, &other.piece_orientations)
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, _)| Piece(i as _))
+            .collect()
+    }
+}
+
 /// Enumeration of all puzzle types.
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum PuzzleTypeEnum {
@@ -282,8 +443,24 @@ impl FromStr for Twist {
     }
 }
 impl Twist {
+    /// Parses a single twist using the notation of a puzzle type.
+    pub fn parse(ty: PuzzleTypeEnum, s: &str) -> Result<Self, String> {
+        ty.notation_scheme().parse_twist(s)
+    }
+    /// Formats the twist using the notation of a puzzle type. The twist is
+    /// canonicalized first, so `Twist::parse()` on the result always returns
+    /// the canonical form of the twist.
+    pub fn to_notation_string(self, ty: PuzzleTypeEnum) -> String {
+        ty.notation_scheme()
+            .twist_to_string(ty.canonicalize_twist(self))
+    }
+
     pub fn from_rng(ty: PuzzleTypeEnum) -> Self {
-        let mut rng = rand::thread_rng();
+        Self::random(ty, &mut rand::thread_rng())
+    }
+    /// Returns a random twist that does not turn the whole puzzle, using the
+    /// given random number generator.
+    pub fn random(ty: PuzzleTypeEnum, rng: &mut impl Rng) -> Self {
         Self {
             axis: TwistAxis(rng.gen_range(0..ty.twist_axes().len()) as _),
             direction: TwistDirection(rng.gen_range(0..ty.twist_directions().len()) as _),
@@ -612,6 +789,22 @@ impl TwistMetric {
     }
 }
 
+/// Returns whether a piece orientation is a rotation, given the axis and sign
+/// of the face that each of the piece's own axes points toward. Each axis must
+/// appear exactly once. Mirrored orientations cannot be reached by any twist,
+/// so any orientation with an odd number of axis swaps and reversals is
+/// rejected.
+pub(crate) fn is_proper_orientation(faces: &[(u8, Sign)]) -> bool {
+    let axes_are_unique = faces.iter().map(|&(axis, _)| axis).all_unique();
+    let swaps = faces
+        .iter()
+        .tuple_combinations()
+        .filter(|((axis1, _), (axis2, _))| axis1 > axis2)
+        .count();
+    let reversals = faces.iter().filter(|&&(_, sign)| sign == Sign::Neg).count();
+    axes_are_unique && (swaps + reversals) % 2 == 0
+}
+
 /// Positive or negative.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Sign {
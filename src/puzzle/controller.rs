@@ -4,7 +4,7 @@ use anyhow::Result;
 use bitvec::bitvec;
 use bitvec::slice::BitSlice;
 use bitvec::vec::BitVec;
-use cgmath::{Deg, InnerSpace, One, Quaternion, Rotation, Rotation3};
+use cgmath::{Deg, InnerSpace, One, Quaternion, Rad, Rotation, Rotation3};
 use instant::Duration;
 use num_enum::FromPrimitive;
 use std::borrow::Cow;
@@ -40,7 +40,9 @@ pub mod interpolate {
 
 use super::*;
 use crate::commands::PARTIAL_SCRAMBLE_MOVE_COUNT_MAX;
-use crate::preferences::{InteractionPreferences, Preferences, ViewPreferences};
+use crate::preferences::{
+    InteractionPreferences, Preferences, StickerColorOverride, ViewPreferences,
+};
 use crate::util;
 use interpolate::InterpolateFn;
 
@@ -69,6 +71,9 @@ pub struct PuzzleController {
     /// was saved in local storage (always `true` on desktop).
     is_unsaved_in_local_storage: bool,
 
+    /// Starting state loaded from a snapshot, which the scramble and undo
+    /// history are applied to, or `None` if the puzzle started solved.
+    initial_state: Option<PuzzleSnapshot>,
     /// Whether the puzzle has been scrambled.
     scramble_state: ScrambleState,
     /// Scramble twists.
@@ -97,6 +102,15 @@ pub struct PuzzleController {
     /// Opacity of hidden pieces preview when hovering over a piece filter
     /// buton.
     hidden_pieces_preview_opacity: Option<f32>,
+    /// Snapshot to compare against, so that only pieces that have changed
+    /// since then are shown (for debugging).
+    changed_pieces_base: Option<PuzzleSnapshot>,
+    /// Twist axis and layer being inspected by scrubbing through layers, if
+    /// any. Pieces in other layers are shown as hidden.
+    layer_scrub: Option<(TwistAxis, u8)>,
+
+    /// Adjacent faces for each face, indexed by face.
+    face_adjacency: Vec<Vec<Face>>,
 
     /// Piece states, such as whether a piece is hidden. All values are
     /// represented as `f32` for animation.
@@ -105,6 +119,10 @@ pub struct PuzzleController {
     /// Cached sticker geometry.
     cached_geometry: Option<Arc<Vec<ProjectedStickerGeometry>>>,
     cached_geometry_params: Option<StickerGeometryParams>,
+    cached_geometry_preview_twist: Option<Twist>,
+    cached_stereo_geometry: Option<StereoGeometryCache>,
+    /// Cached sticker colors.
+    cached_sticker_colors: Option<StickerColorsCache>,
 }
 impl Default for PuzzleController {
     fn default() -> Self {
@@ -125,8 +143,12 @@ impl PartialEq<Puzzle> for PuzzleController {
 impl PuzzleController {
     /// Constructs a new PuzzleController with a solved puzzle.
     pub fn new(ty: PuzzleTypeEnum) -> Self {
+        Self::with_puzzle(Puzzle::new(ty))
+    }
+    fn with_puzzle(puzzle: Puzzle) -> Self {
+        let ty = puzzle.ty();
         Self {
-            puzzle: Puzzle::new(ty),
+            puzzle,
             twist_anim: TwistAnimationState::default(),
             view_settings_anim: ViewSettingsAnimState::default(),
             view_angle: ViewAngleAnimState::default(),
@@ -135,6 +157,7 @@ impl PuzzleController {
             is_unsaved_via_clipboard: true,
             is_unsaved_in_local_storage: true,
 
+            initial_state: None,
             scramble_state: ScrambleState::None,
             scramble: vec![],
             undo_buffer: vec![],
@@ -149,16 +172,37 @@ impl PuzzleController {
             visible_pieces: bitvec![1; ty.pieces().len()],
             visible_pieces_preview: None,
             hidden_pieces_preview_opacity: None,
+            changed_pieces_base: None,
+            layer_scrub: None,
+
+            face_adjacency: ty.face_adjacency(),
 
             visual_piece_states: vec![VisualPieceState::default(); ty.pieces().len()],
 
             cached_geometry: None,
             cached_geometry_params: None,
+            cached_geometry_preview_twist: None,
+            cached_stereo_geometry: None,
+            cached_sticker_colors: None,
         }
     }
-    /// Resets the puzzle.
+    /// Constructs a new PuzzleController that starts from the state in a
+    /// snapshot. The puzzle is marked as scrambled unless the state is solved.
+    pub fn from_snapshot(snapshot: &PuzzleSnapshot) -> Result<Self, &'static str> {
+        let mut ret = Self::new(snapshot.ty);
+        ret.display_snapshot(snapshot)?;
+        if !ret.is_solved() {
+            ret.add_scramble_marker(ScrambleState::Full);
+        }
+        Ok(ret)
+    }
+    /// Resets the puzzle to its solved state and clears the undo history,
+    /// scramble, and all other state, such as the grip, selection, and hidden
+    /// pieces.
     pub fn reset(&mut self) {
-        *self = Self::new(self.ty());
+        let mut puzzle = self.puzzle.clone();
+        puzzle.reset();
+        *self = Self::with_puzzle(puzzle);
     }
 
     /// Returns whether the puzzle has been scrambled, solved, etc..
@@ -204,14 +248,48 @@ impl PuzzleController {
 
     /// Adds a twist to the back of the twist queue.
     pub fn twist(&mut self, twist: Twist) -> Result<(), &'static str> {
-        self._twist(twist, true)
+        self._twist(twist, true, false)
     }
     /// Adds a twist to the back of the twist queue. Does not cancel adjacent
     /// twists.
     pub fn twist_no_collapse(&mut self, twist: Twist) -> Result<(), &'static str> {
-        self._twist(twist, false)
+        self._twist(twist, false, false)
+    }
+    /// Adds a twist from user input to the back of the twist queue. If
+    /// `blocking` is `true`, then other user input is rejected until its
+    /// animation is complete. Returns an error if user input is currently
+    /// blocked.
+    pub fn twist_from_input(&mut self, twist: Twist, blocking: bool) -> Result<(), &'static str> {
+        self.check_input_not_blocked()?;
+        self._twist(twist, true, blocking)
+    }
+    /// Reverses the direction of a twist from user input if its twist axis is
+    /// reversed in the interaction preferences.
+    pub fn apply_axis_reversal(&self, twist: Twist, prefs: &InteractionPreferences) -> Twist {
+        if prefs.is_axis_reversed(self.info(twist.axis).name) {
+            self.reverse_twist(twist)
+        } else {
+            twist
+        }
     }
-    fn _twist(&mut self, mut twist: Twist, collapse: bool) -> Result<(), &'static str> {
+    /// Returns whether a blocking twist is animating, in which case user input
+    /// is rejected.
+    pub fn is_blocked(&self) -> bool {
+        self.twist_anim.queue.iter().any(|anim| anim.is_blocking)
+    }
+    fn check_input_not_blocked(&self) -> Result<(), &'static str> {
+        if self.is_blocked() {
+            Err("Input is blocked until the twist animation is complete")
+        } else {
+            Ok(())
+        }
+    }
+    fn _twist(
+        &mut self,
+        mut twist: Twist,
+        collapse: bool,
+        is_blocking: bool,
+    ) -> Result<(), &'static str> {
         twist.layers &= self.all_layers(); // Restrict layer mask.
         if twist.layers == LayerMask(0) {
             return Err("invalid layer mask");
@@ -224,9 +302,9 @@ impl PuzzleController {
         if collapse && self.undo_buffer.last() == Some(&self.reverse_twist(twist).into()) {
             // This twist is the reverse of the last one, so just undo the last
             // one.
-            self.undo()
+            self._undo(is_blocking)
         } else {
-            self.animate_twist(twist)?;
+            self.animate_twist(twist, is_blocking)?;
             self.undo_buffer.push(twist.into());
             Ok(())
         }
@@ -266,13 +344,14 @@ impl PuzzleController {
     }
     /// Applies a twist to the puzzle and queues it for animation. Does _not_
     /// handle undo/redo stack or `is_unsaved`.
-    fn animate_twist(&mut self, twist: Twist) -> Result<(), &'static str> {
+    fn animate_twist(&mut self, twist: Twist, is_blocking: bool) -> Result<(), &'static str> {
         let old_state = self.puzzle.clone();
         self.puzzle.twist(twist)?;
         self.twist_anim.queue.push_back(TwistAnimation {
             state: old_state,
             twist,
             view_angle_offset_delta: Quaternion::one(),
+            is_blocking,
         });
 
         // Invalidate the cache.
@@ -310,6 +389,95 @@ impl PuzzleController {
         &self.puzzle
     }
 
+    /// Returns a snapshot of the displayed puzzle state, not including the
+    /// twist currently being animated (if there is one).
+    pub fn snapshot(&self) -> PuzzleSnapshot {
+        self.displayed().snapshot()
+    }
+    /// Displays the puzzle state from a snapshot, skipping any queued twist
+    /// animations. The undo history and scramble are cleared, since they do
+    /// not lead to the new state, and the snapshot becomes the initial state.
+    pub fn display_snapshot(&mut self, snapshot: &PuzzleSnapshot) -> Result<(), &'static str> {
+        self.puzzle.load_snapshot(snapshot)?;
+        self.skip_twist_animations();
+        self.initial_state = Some(snapshot.clone());
+        self.undo_buffer.clear();
+        self.redo_buffer.clear();
+        self.scramble.clear();
+        self.scramble_state = ScrambleState::None;
+        self.cached_geometry = None;
+        self.mark_unsaved();
+        Ok(())
+    }
+    /// Returns the state that the scramble and undo history are applied to, or
+    /// `None` if it is the solved state.
+    pub fn initial_state(&self) -> Option<&PuzzleSnapshot> {
+        self.initial_state.as_ref()
+    }
+
+    /// Returns the snapshot that pieces are compared against when showing
+    /// only changed pieces.
+    pub fn changed_pieces_base(&self) -> Option<&PuzzleSnapshot> {
+        self.changed_pieces_base.as_ref()
+    }
+    /// Sets the snapshot to compare pieces against, or `None` to show all
+    /// pieces normally.
+    pub fn set_changed_pieces_base(&mut self, base: Option<PuzzleSnapshot>) {
+        self.changed_pieces_base = base;
+    }
+    /// Returns the set of pieces in the displayed puzzle state that have
+    /// changed since the snapshot set using `set_changed_pieces_base()`, or
+    /// `None` if there is no such snapshot.
+    pub fn changed_pieces(&self) -> Option<BitVec> {
+        let base = self.changed_pieces_base.as_ref()?;
+        let mut ret = bitvec![0; self.ty().pieces().len()];
+        for piece in base.diff(&self.snapshot()) {
+            ret.set(piece.0 as usize, true);
+        }
+        Some(ret)
+    }
+
+    /// Returns the twist axis and layer mask being inspected by scrubbing
+    /// through layers, if any.
+    pub fn layer_scrub(&self) -> Option<(TwistAxis, LayerMask)> {
+        self.layer_scrub
+            .map(|(axis, layer)| (axis, LayerMask(1 << layer)))
+    }
+    /// Moves the layer being inspected along a twist axis by `steps` layers,
+    /// starting from the outermost layer in the direction of `steps` if no
+    /// layer along that axis is being inspected yet.
+    pub fn scrub_layers(&mut self, axis: TwistAxis, steps: i32) {
+        let max_layer = self.layer_count() as i32 - 1;
+        let layer = match self.layer_scrub {
+            Some((old_axis, layer)) if old_axis == axis => layer as i32 + steps,
+            _ if steps < 0 => max_layer,
+            _ => 0,
+        };
+        self.layer_scrub = Some((axis, layer.clamp(0, max_layer) as u8));
+    }
+    /// Stops scrubbing through layers and shows all pieces normally.
+    pub fn stop_layer_scrub(&mut self) {
+        self.layer_scrub = None;
+    }
+    /// Returns whether a piece is outside the layer being inspected by
+    /// scrubbing through layers.
+    pub fn is_hidden_by_layer_scrub(&self, piece: Piece) -> bool {
+        match self.layer_scrub() {
+            Some((axis, layers)) => !layers[self.displayed().layer_from_twist_axis(axis, piece)],
+            None => false,
+        }
+    }
+
+    /// Starts searching for a short sequence of twists that makes progress
+    /// toward solving the latest puzzle state, or returns `None` if no hint is
+    /// available.
+    pub fn hint_search(&self) -> Option<HintSearch> {
+        match self.latest() {
+            Puzzle::Rubiks3D(p) => p.hint_search(),
+            Puzzle::Rubiks4D(_) => None,
+        }
+    }
+
     /// Returns the puzzle type.
     pub fn ty(&self) -> PuzzleTypeEnum {
         self.puzzle.ty()
@@ -332,11 +500,14 @@ impl PuzzleController {
     /// Sets the view angle offset. Consider calling
     /// `freeze_view_angle_offset()` as well.
     pub fn add_view_angle_offset(&mut self, offset: [f32; 2], view_prefs: &ViewPreferences) {
-        let prefs_view_angle = view_prefs.view_angle();
         let offset =
             Quaternion::from_angle_x(Deg(offset[1])) * Quaternion::from_angle_y(Deg(offset[0]));
+        let prefs_view_angle = view_prefs.view_angle();
+        // Renormalize so that numerical error doesn't accumulate over many
+        // small rotations and skew the puzzle.
         self.view_angle.current =
-            prefs_view_angle.invert() * offset * prefs_view_angle * self.view_angle.current;
+            (prefs_view_angle.invert() * offset * prefs_view_angle * self.view_angle.current)
+                .normalize();
     }
     /// Freezes the view angle offset, so that it will not animate back to zero
     /// automatically. It can still be changed with `set_view_angle_offset()`.
@@ -349,11 +520,23 @@ impl PuzzleController {
         self.apply_transient_rotation();
         self.view_angle.is_frozen = false;
     }
+    /// Ends dragging the view, and realigns the puzzle if that is enabled in
+    /// the interaction preferences.
+    pub fn release_view_angle_offset(&mut self, prefs: &InteractionPreferences) {
+        if prefs.realign_on_release {
+            self.unfreeze_view_angle_offset();
+        }
+    }
     fn update_transient_rotation(&mut self, interaction_prefs: &InteractionPreferences) {
         if interaction_prefs.smart_realign {
             let nearest_twists = self.puzzle.nearest_rotation(self.view_angle.current);
+            // The scalar part of a unit quaternion is the cosine of half the
+            // angle of rotation.
+            let remaining = self.view_angle.current.invert() * nearest_twists.1;
+            let remaining_angle = Rad(2.0 * remaining.s.abs().min(1.0).acos());
+            let is_near = Deg::from(remaining_angle).0 <= interaction_prefs.smart_realign_threshold;
             self.view_angle.transient_rotation =
-                (!nearest_twists.0.is_empty()).then_some(nearest_twists);
+                (!nearest_twists.0.is_empty() && is_near).then_some(nearest_twists);
         } else {
             self.view_angle.transient_rotation = None;
         }
@@ -393,6 +576,23 @@ impl PuzzleController {
     pub(crate) fn hovered_sticker(&self) -> Option<Sticker> {
         self.hovered_sticker
     }
+    /// Returns the piece, sticker, and face color name of the hovered
+    /// sticker, or `None` if no sticker is hovered.
+    pub fn hovered_sticker_info(&self) -> Option<(Piece, Sticker, &'static str)> {
+        let sticker = self.hovered_sticker?;
+        let sticker_info = self.info(sticker);
+        Some((
+            sticker_info.piece,
+            sticker,
+            self.info(sticker_info.color).name,
+        ))
+    }
+    /// Returns the faces adjacent to the face of the hovered sticker, or
+    /// `None` if no sticker is hovered.
+    pub fn hovered_sticker_adjacent_faces(&self) -> Option<&[Face]> {
+        let sticker = self.hovered_sticker?;
+        Some(&self.face_adjacency[self.info(sticker).color.0 as usize])
+    }
     pub(crate) fn hovered_twists(&self) -> Option<ClickTwists> {
         self.hovered_twists
     }
@@ -431,100 +631,236 @@ impl PuzzleController {
             self.view_angle.current * self.view_angle.queued_delta,
         );
 
-        if self.cached_geometry_params != Some(params) {
+        let preview_twist = self.twist_preview(prefs);
+
+        if self.cached_geometry_params != Some(params)
+            || self.cached_geometry_preview_twist != preview_twist
+        {
             // Invalidate the cache.
             self.cached_geometry = None;
         }
 
         self.cached_geometry_params = Some(params);
+        self.cached_geometry_preview_twist = preview_twist;
 
         let ret = self.cached_geometry.take().unwrap_or_else(|| {
             log::trace!("Regenerating puzzle geometry");
+            Arc::new(self.generate_geometry(prefs, params, preview_twist))
+        });
+
+        self.cached_geometry = Some(Arc::clone(&ret));
+        ret
+    }
+
+    /// Returns puzzle geometry as seen by the left and right eyes of a stereo
+    /// camera, offset horizontally from the center by half the eye separation
+    /// in the graphics preferences.
+    pub(crate) fn stereo_geometry(
+        &mut self,
+        prefs: &Preferences,
+    ) -> [Arc<Vec<ProjectedStickerGeometry>>; 2] {
+        // The stereo geometry is stale whenever the mono geometry is
+        // regenerated.
+        let mono = self.geometry(prefs);
+        let eye_separation = prefs.gfx.stereo_eye_separation;
+        if let Some(cache) = &self.cached_stereo_geometry {
+            if Arc::ptr_eq(&cache.mono, &mono) && cache.eye_separation == eye_separation {
+                return cache.eyes.clone();
+            }
+        }
+
+        log::trace!("Regenerating stereo puzzle geometry");
+        let view_prefs = self.view_prefs(prefs);
+        let params = StickerGeometryParams::new(
+            &view_prefs,
+            self.ty(),
+            self.current_twist(),
+            self.view_angle.current * self.view_angle.queued_delta,
+        );
+        let preview_twist = self.twist_preview(prefs);
+        let half_separation = eye_separation / 2.0;
+        let eyes = [-half_separation, half_separation].map(|eye_offset| {
+            let params = StickerGeometryParams {
+                eye_offset,
+                ..params
+            };
+            Arc::new(self.generate_geometry(prefs, params, preview_twist))
+        });
+
+        self.cached_stereo_geometry = Some(StereoGeometryCache {
+            mono,
+            eye_separation,
+            eyes: eyes.clone(),
+        });
+        eyes
+    }
+    /// Returns the fill color of each sticker, indexed by sticker, ignoring
+    /// blindfold mode. Custom sticker colors take precedence over face colors.
+    pub(crate) fn sticker_colors(&mut self, prefs: &Preferences) -> Arc<Vec<egui::Color32>> {
+        let ty = self.ty();
+        let face_colors = prefs.colors.face_colors_list(ty);
+        let overrides = &prefs.colors.sticker_overrides[ty];
+        if let Some(cache) = &self.cached_sticker_colors {
+            if cache.face_colors == face_colors && cache.overrides == *overrides {
+                return Arc::clone(&cache.colors);
+            }
+        }
+
+        log::trace!("Regenerating sticker colors");
+        let mut colors: Vec<_> = self
+            .stickers()
+            .iter()
+            .map(|info| face_colors[info.color.0 as usize])
+            .collect();
+        for o in overrides {
+            if let Some(color) = colors.get_mut(o.sticker as usize) {
+                *color = o.color;
+            }
+        }
+        let colors = Arc::new(colors);
+
+        self.cached_sticker_colors = Some(StickerColorsCache {
+            face_colors,
+            overrides: overrides.clone(),
+            colors: Arc::clone(&colors),
+        });
+        colors
+    }
+
+    /// Projects and depth-sorts all visible stickers.
+    fn generate_geometry(
+        &self,
+        prefs: &Preferences,
+        params: StickerGeometryParams,
+        preview_twist: Option<Twist>,
+    ) -> Vec<ProjectedStickerGeometry> {
+        // Project stickers.
+        let mut sticker_geometries: Vec<ProjectedStickerGeometry> = vec![];
+        for sticker in (0..self.stickers().len() as _).map(Sticker) {
+            let piece = self.info(sticker).piece;
+            let vis_piece = self.visual_piece_state(piece);
+            if !self.is_sticker_hoverable(sticker) && vis_piece.opacity(prefs) == 0.0 {
+                continue;
+            }
 
-            // Project stickers.
-            let mut sticker_geometries: Vec<ProjectedStickerGeometry> = vec![];
+            sticker_geometries.extend(self.project_sticker(sticker, params));
+        }
+
+        // Sort stickers by depth.
+        geometry::sort_by_depth(&mut sticker_geometries);
+
+        // Project stickers for the twist preview, which are drawn on top of
+        // everything else.
+        if let Some(twist) = preview_twist {
+            let preview_params = StickerGeometryParams {
+                twist_animation: Some((twist, 1.0)),
+                ..params
+            };
+            let mut preview_geometries: Vec<ProjectedStickerGeometry> = vec![];
             for sticker in (0..self.stickers().len() as _).map(Sticker) {
                 let piece = self.info(sticker).piece;
                 let vis_piece = self.visual_piece_state(piece);
-                if !self.is_sticker_hoverable(sticker) && vis_piece.opacity(prefs) == 0.0 {
+                if !self.displayed().is_piece_affected_by_twist(twist, piece)
+                    || vis_piece.opacity(prefs) == 0.0
+                {
                     continue;
                 }
 
-                // Compute geometry, including vertex positions before 3D
-                // perspective projection.
-                let sticker_geom = match self.displayed().sticker_geometry(sticker, params) {
-                    Some(s) => s,
-                    None => continue, // invisible; skip this sticker
-                };
-
-                // Compute vertex positions after 3D perspective projection.
-                let projected_verts = match sticker_geom
-                    .verts
-                    .iter()
-                    .map(|&v| params.project_3d(v))
-                    .collect::<Option<Vec<_>>>()
-                {
-                    Some(s) => s,
-                    None => continue, // behind camera; skip this sticker
-                };
-
-                let mut projected_front_polygons = vec![];
-                let mut projected_back_polygons = vec![];
-
-                for (indices, twists) in sticker_geom
-                    .polygon_indices
-                    .iter()
-                    .zip(sticker_geom.polygon_twists)
-                {
-                    let projected_normal =
-                        geometry::polygon_normal_from_indices(&projected_verts, indices);
-                    if projected_normal.z > 0.0 {
-                        // This polygon is front-facing.
-                        let lighting_normal =
-                            geometry::polygon_normal_from_indices(&sticker_geom.verts, indices)
-                                .normalize();
-                        let illumination =
-                            params.ambient_light + lighting_normal.dot(params.light_vector);
-                        projected_front_polygons.push(geometry::polygon_from_indices(
-                            &projected_verts,
-                            indices,
-                            illumination,
-                            twists,
-                        ));
-                    } else {
-                        // This polygon is back-facing.
-                        let illumination = 0.0; // don't care
-                        projected_back_polygons.push(geometry::polygon_from_indices(
-                            &projected_verts,
-                            indices,
-                            illumination,
-                            ClickTwists::default(), // don't care
-                        ));
-                    }
+                if let Some(geom) = self.project_sticker(sticker, preview_params) {
+                    preview_geometries.push(ProjectedStickerGeometry {
+                        is_preview: true,
+                        ..geom
+                    });
                 }
+            }
+            geometry::sort_by_depth(&mut preview_geometries);
+            sticker_geometries.extend(preview_geometries);
+        }
 
-                let (min_bound, max_bound) = util::min_and_max_bound(&projected_verts);
+        sticker_geometries
+    }
+
+    /// Projects a single sticker using the given geometry parameters. Returns
+    /// `None` if the sticker is invisible or behind the camera.
+    fn project_sticker(
+        &self,
+        sticker: Sticker,
+        params: StickerGeometryParams,
+    ) -> Option<ProjectedStickerGeometry> {
+        // Compute geometry, including vertex positions before 3D
+        // perspective projection. If this is `None`, then the sticker is
+        // invisible.
+        let sticker_geom = self.displayed().sticker_geometry(sticker, params)?;
+
+        // Compute vertex positions after 3D perspective projection. If this is
+        // `None`, then the sticker is behind the camera.
+        let projected_verts = sticker_geom
+            .verts
+            .iter()
+            .map(|&v| params.project_3d(v))
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut projected_front_polygons = vec![];
+        let mut projected_back_polygons = vec![];
+
+        for (indices, twists) in sticker_geom
+            .polygon_indices
+            .iter()
+            .zip(sticker_geom.polygon_twists)
+        {
+            let projected_normal = geometry::polygon_normal_from_indices(&projected_verts, indices);
+            if projected_normal.z > 0.0 {
+                // This polygon is front-facing.
+                let lighting_normal =
+                    geometry::polygon_normal_from_indices(&sticker_geom.verts, indices).normalize();
+                let illumination = params.illumination(lighting_normal);
+                projected_front_polygons.push(geometry::polygon_from_indices(
+                    &projected_verts,
+                    indices,
+                    illumination,
+                    twists,
+                ));
+            } else {
+                // This polygon is back-facing.
+                let illumination = 0.0; // don't care
+                projected_back_polygons.push(geometry::polygon_from_indices(
+                    &projected_verts,
+                    indices,
+                    illumination,
+                    ClickTwists::default(), // don't care
+                ));
+            }
+        }
 
-                sticker_geometries.push(ProjectedStickerGeometry {
-                    sticker,
+        let (min_bound, max_bound) = util::min_and_max_bound(&projected_verts);
 
-                    verts: projected_verts.into_boxed_slice(),
-                    min_bound,
-                    max_bound,
+        Some(ProjectedStickerGeometry {
+            sticker,
 
-                    front_polygons: projected_front_polygons.into_boxed_slice(),
-                    back_polygons: projected_back_polygons.into_boxed_slice(),
-                });
-            }
+            verts: projected_verts.into_boxed_slice(),
+            min_bound,
+            max_bound,
 
-            // Sort stickers by depth.
-            geometry::sort_by_depth(&mut sticker_geometries);
+            front_polygons: projected_front_polygons.into_boxed_slice(),
+            back_polygons: projected_back_polygons.into_boxed_slice(),
 
-            Arc::new(sticker_geometries)
-        });
+            is_preview: false,
+        })
+    }
 
-        self.cached_geometry = Some(Arc::clone(&ret));
-        ret
+    /// Returns the twist to preview when hovering over a sticker, if enabled.
+    /// Pieces affected by this twist are drawn again, translucently, in the
+    /// position they would be in after the twist.
+    pub(crate) fn twist_preview(&self, prefs: &Preferences) -> Option<Twist> {
+        if !prefs.interaction.preview_hovered_twist || !self.twist_anim.queue.is_empty() {
+            return None;
+        }
+        let mut twist = self.hovered_twists?.cw?;
+        if twist.layers == LayerMask::default() {
+            twist.layers = self.grip.layers.unwrap_or_default();
+        }
+        twist.layers &= self.all_layers();
+        (twist.layers != LayerMask(0)).then(|| self.canonicalize_twist(twist))
     }
 
     /// Advances the puzzle geometry and internal state to the next frame, using
@@ -565,7 +901,10 @@ impl PuzzleController {
                 true => ((anim.queue.len() - 1) as f32 * EXP_TWIST_FACTOR).exp(),
                 false => 1.0,
             };
-            let mut twist_delta = base_speed * speed_mod;
+            // Use the twist duration for the axis of the current twist.
+            let axis_name = anim.queue[0].state.info(anim.queue[0].twist.axis).name;
+            let twist_speed = delta.as_secs_f32() / prefs.twist_duration_for_axis(axis_name);
+            let mut twist_delta = twist_speed * speed_mod;
             // Cap the twist delta at 1.0, and also handle the case where
             // something went wrong with the calculation (e.g., division by
             // zero).
@@ -746,12 +1085,22 @@ impl PuzzleController {
     /// Undoes one twist. Returns an error if there was nothing to undo or the
     /// twist could not be applied to the puzzle.
     pub fn undo(&mut self) -> Result<(), &'static str> {
+        self._undo(false)
+    }
+    /// Undoes one twist from user input. If `blocking` is `true`, then other
+    /// user input is rejected until its animation is complete. Returns an
+    /// error if user input is currently blocked.
+    pub fn undo_from_input(&mut self, blocking: bool) -> Result<(), &'static str> {
+        self.check_input_not_blocked()?;
+        self._undo(blocking)
+    }
+    fn _undo(&mut self, is_blocking: bool) -> Result<(), &'static str> {
         if let Some(entry) = self.undo_buffer.pop() {
             self.mark_unsaved();
             match entry {
                 HistoryEntry::Twist(twist) => {
                     let rev = self.reverse_twist(twist);
-                    self.animate_twist(rev)?;
+                    self.animate_twist(rev, is_blocking)?;
                 }
             }
             self.redo_buffer.push(entry);
@@ -763,10 +1112,20 @@ impl PuzzleController {
     /// Redoes one twist. Returns an error if there was nothing to redo or the
     /// twist could not be applied to the puzzle.
     pub fn redo(&mut self) -> Result<(), &'static str> {
+        self._redo(false)
+    }
+    /// Redoes one twist from user input. If `blocking` is `true`, then other
+    /// user input is rejected until its animation is complete. Returns an
+    /// error if user input is currently blocked.
+    pub fn redo_from_input(&mut self, blocking: bool) -> Result<(), &'static str> {
+        self.check_input_not_blocked()?;
+        self._redo(blocking)
+    }
+    fn _redo(&mut self, is_blocking: bool) -> Result<(), &'static str> {
         if let Some(entry) = self.redo_buffer.pop() {
             self.mark_unsaved();
             match entry {
-                HistoryEntry::Twist(twist) => self.animate_twist(twist)?,
+                HistoryEntry::Twist(twist) => self.animate_twist(twist, is_blocking)?,
             }
             self.undo_buffer.push(entry);
             Ok(())
@@ -816,6 +1175,13 @@ impl PuzzleController {
             }
         }
     }
+    /// Returns whether discarding the puzzle state, such as by resetting it,
+    /// should be confirmed first. If `only_when_scrambled` is `true`, then
+    /// changes are discarded without confirmation unless the puzzle has been
+    /// fully scrambled.
+    pub fn should_confirm_discard(&self, only_when_scrambled: bool) -> bool {
+        self.is_unsaved() && !(only_when_scrambled && !self.has_been_fully_scrambled())
+    }
     /// Returns whether the puzzle has been solved at some point.
     pub fn has_been_solved(&self) -> bool {
         self.scramble_state == ScrambleState::Solved
@@ -896,6 +1262,30 @@ struct TwistAnimation {
     twist: Twist,
     /// Delta to apply to the view angle before animating.
     view_angle_offset_delta: Quaternion<f32>,
+    /// Whether other twists are ignored until this one is done animating.
+    is_blocking: bool,
+}
+
+/// Cached sticker geometry for both eyes of a stereo camera.
+#[derive(Debug, Clone)]
+struct StereoGeometryCache {
+    /// Mono geometry that was cached when this was generated.
+    mono: Arc<Vec<ProjectedStickerGeometry>>,
+    /// Horizontal distance between the eyes.
+    eye_separation: f32,
+    /// Geometry for the left and right eyes.
+    eyes: [Arc<Vec<ProjectedStickerGeometry>>; 2],
+}
+
+/// Cached fill color of each sticker.
+#[derive(Debug, Clone)]
+struct StickerColorsCache {
+    /// Face colors that were used to generate this.
+    face_colors: Vec<egui::Color32>,
+    /// Custom sticker colors that were used to generate this.
+    overrides: Vec<StickerColorOverride>,
+    /// Fill color of each sticker, indexed by sticker.
+    colors: Arc<Vec<egui::Color32>>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -1106,12 +1496,17 @@ pub struct VisualPieceState {
     hidden_opacity_override: Option<f32>,
 }
 impl VisualPieceState {
-    pub fn outline_color(self, prefs: &Preferences, is_sticker_selected: bool) -> egui::Rgba {
+    pub fn outline_color(
+        self,
+        prefs: &Preferences,
+        face_color: egui::Color32,
+        is_sticker_selected: bool,
+    ) -> egui::Rgba {
         let pr = &prefs.outlines;
 
         let hidden_or_ungripped = f32::max(self.hidden, self.ungripped);
 
-        let mut ret = egui::Rgba::from(pr.default_color);
+        let mut ret = pr.default_color_for_face(face_color);
         // In order from lowest to highest priority:
         ret = util::mix(ret, egui::Rgba::from(pr.hidden_color), hidden_or_ungripped);
         ret = util::mix(ret, egui::Rgba::from(pr.hovered_color), self.hovered);
@@ -1164,3 +1559,561 @@ impl VisualPieceState {
         ret
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view_angle_after(f: impl FnOnce(&mut PuzzleController)) -> Quaternion<f32> {
+        let mut puzzle = PuzzleController::default();
+        f(&mut puzzle);
+        puzzle.view_angle.current
+    }
+
+    #[test]
+    fn test_view_angle_roll() {
+        type Step = fn(&mut ViewPreferences, &mut PuzzleController);
+        fn roll(v: &mut ViewPreferences, _: &mut PuzzleController) {
+            v.add_roll(30.0);
+        }
+        fn zoom(v: &mut ViewPreferences, _: &mut PuzzleController) {
+            v.scale *= 2.0;
+        }
+        fn yaw(v: &mut ViewPreferences, p: &mut PuzzleController) {
+            p.add_view_angle_offset([45.0, 0.0], v);
+        }
+
+        // Returns the view preferences and the orientation of the puzzle on
+        // screen after some steps.
+        let after = |steps: &[Step]| {
+            let mut view_prefs = ViewPreferences {
+                pitch: 20.0,
+                yaw: -30.0,
+                ..Default::default()
+            };
+            let mut puzzle = PuzzleController::default();
+            for step in steps {
+                step(&mut view_prefs, &mut puzzle);
+            }
+            let displayed = view_prefs.view_angle() * puzzle.view_angle.current;
+            (view_prefs, displayed)
+        };
+
+        // Roll is stored in the view preferences, not the temporary offset,
+        // and rolls compose additively.
+        let (view_prefs, a) = after(&[roll, roll]);
+        assert_eq!(view_prefs.roll, 60.0);
+        let (_, b) = after(&[|v, _| v.add_roll(60.0)]);
+        assert!((a - b).magnitude() < 0.0001);
+
+        // Roll commutes with zoom.
+        let (prefs_a, a) = after(&[roll, zoom]);
+        let (prefs_b, b) = after(&[zoom, roll]);
+        assert_eq!(prefs_a, prefs_b);
+        assert!((a - b).magnitude() < 0.0001);
+
+        // Roll does not commute with yaw.
+        let (_, a) = after(&[roll, yaw]);
+        let (_, b) = after(&[yaw, roll]);
+        assert!((a - b).magnitude() > 0.01);
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 3 },
+        ] {
+            let mut scrambled = PuzzleController::new(ty);
+            let twists: Vec<_> = scrambled.latest().legal_twists().step_by(7).collect();
+            for twist in twists {
+                scrambled.twist(twist).unwrap();
+            }
+            scrambled.skip_twist_animations();
+
+            let serialized = serde_yaml::to_string(&scrambled.snapshot()).unwrap();
+            let snapshot: PuzzleSnapshot = serde_yaml::from_str(&serialized).unwrap();
+
+            let mut puzzle = PuzzleController::new(ty);
+            assert_ne!(puzzle, scrambled);
+            puzzle.display_snapshot(&snapshot).unwrap();
+            assert_eq!(puzzle, scrambled);
+
+            let mut other_puzzle =
+                PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 2 });
+            assert!(other_puzzle.display_snapshot(&snapshot).is_err());
+        }
+    }
+
+    #[test]
+    fn test_snapshot_clears_history() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let snapshot = PuzzleController::new(ty).snapshot();
+
+        let mut puzzle = PuzzleController::new(ty);
+        puzzle.scramble_n(5).unwrap();
+        let twist = puzzle.latest().legal_twists().next().unwrap();
+        puzzle.twist(twist).unwrap();
+        puzzle.display_snapshot(&snapshot).unwrap();
+        assert!(!puzzle.has_undo());
+        assert!(!puzzle.has_redo());
+        assert_eq!(puzzle.scramble_state(), ScrambleState::None);
+        assert!(puzzle.undo().is_err());
+    }
+
+    #[test]
+    fn test_snapshot_rejects_mirrored_orientation() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 3 },
+        ] {
+            let mut puzzle = PuzzleController::new(ty);
+            let solved = puzzle.snapshot();
+            let with_piece_0 = |f: fn(&mut Vec<u8>)| {
+                let mut snapshot = solved.clone();
+                f(&mut snapshot.piece_orientations[0]);
+                snapshot
+            };
+
+            // Reversing one axis is a reflection.
+            let mirrored = with_piece_0(|faces| faces[0] ^= 1);
+            assert!(puzzle.display_snapshot(&mirrored).is_err());
+            // Swapping two axes is a reflection.
+            let mirrored = with_piece_0(|faces| faces.swap(0, 1));
+            assert!(puzzle.display_snapshot(&mirrored).is_err());
+            // Doing both to every piece rotates the whole puzzle.
+            let mut rotated = solved.clone();
+            for faces in &mut rotated.piece_orientations {
+                faces.swap(0, 1);
+                faces[0] ^= 1;
+            }
+            assert!(puzzle.display_snapshot(&rotated).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_snapshot_rejects_colliding_pieces() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 3 },
+        ] {
+            let mut puzzle = PuzzleController::new(ty);
+            let solved = puzzle.snapshot();
+            let mut twisted_state = Puzzle::new(ty);
+            twisted_state
+                .twist(Twist {
+                    axis: TwistAxis(0),
+                    direction: TwistDirection(0),
+                    layers: LayerMask(1),
+                })
+                .unwrap();
+            let twisted = twisted_state.snapshot();
+
+            // Move one piece without moving the piece already in its new
+            // location.
+            let moved_piece = solved
+                .diff(&twisted)
+                .into_iter()
+                .find(|&piece| {
+                    (0..puzzle.twist_axes().len() as _)
+                        .map(TwistAxis)
+                        .any(|axis| {
+                            twisted_state.layer_from_twist_axis(axis, piece)
+                                != puzzle.latest().layer_from_twist_axis(axis, piece)
+                        })
+                })
+                .unwrap();
+            let mut collided = solved.clone();
+            collided.piece_orientations[moved_piece.0 as usize] =
+                twisted.piece_orientations[moved_piece.0 as usize].clone();
+            assert_eq!(
+                puzzle.display_snapshot(&collided),
+                Err("snapshot has multiple pieces in the same location"),
+            );
+            assert_eq!(puzzle.snapshot(), solved);
+
+            assert!(puzzle.display_snapshot(&twisted).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_changed_pieces() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 3 },
+        ] {
+            let mut puzzle = PuzzleController::new(ty);
+            assert_eq!(puzzle.changed_pieces(), None);
+
+            puzzle.set_changed_pieces_base(Some(puzzle.snapshot()));
+            assert!(puzzle.changed_pieces().unwrap().not_any());
+
+            let twist = Twist {
+                axis: TwistAxis(0),
+                direction: TwistDirection(0),
+                layers: LayerMask(1),
+            };
+            puzzle.twist(twist).unwrap();
+            puzzle.skip_twist_animations();
+
+            let expected = puzzle.latest().pieces_affected_by_twist(twist);
+            let changed = puzzle.changed_pieces().unwrap();
+            assert_eq!(
+                changed
+                    .iter_ones()
+                    .map(|i| Piece(i as _))
+                    .collect::<Vec<_>>(),
+                expected,
+            );
+        }
+    }
+
+    #[test]
+    fn test_hovered_sticker_info() {
+        let prefs = &*crate::preferences::DEFAULT_PREFS;
+        let mut puzzle = PuzzleController::default();
+        assert_eq!(puzzle.hovered_sticker_info(), None);
+
+        // Hover over the middle of the frontmost sticker.
+        let geometry = puzzle.geometry(prefs);
+        let front = geometry.last().unwrap();
+        let polygon = &front.front_polygons[0];
+        let n = polygon.verts.len() as f32;
+        let point = polygon
+            .verts
+            .iter()
+            .fold(cgmath::point2(0.0, 0.0), |sum, v| {
+                sum + cgmath::vec2(v.x, v.y) / n
+            });
+        puzzle.update_hovered_sticker(
+            geometry
+                .iter()
+                .rev()
+                .filter_map(|geom| Some((geom.sticker, geom.twists_for_point(point)?))),
+        );
+
+        let (piece, sticker, face_name) = puzzle.hovered_sticker_info().unwrap();
+        assert_eq!(sticker, front.sticker);
+        assert_eq!(piece, puzzle.info(sticker).piece);
+        assert_eq!(face_name, puzzle.info(puzzle.info(sticker).color).name);
+
+        // Nothing is hovered over the background.
+        let background = cgmath::point2(100.0, 100.0);
+        puzzle.update_hovered_sticker(
+            geometry
+                .iter()
+                .rev()
+                .filter_map(|geom| Some((geom.sticker, geom.twists_for_point(background)?))),
+        );
+        assert_eq!(puzzle.hovered_sticker_info(), None);
+    }
+
+    #[test]
+    fn test_view_angle_stays_normalized() {
+        let view_prefs = ViewPreferences {
+            pitch: 20.0,
+            yaw: -30.0,
+            ..Default::default()
+        };
+        let q = view_angle_after(|p| {
+            for i in 0..10_000 {
+                let x = (i as f32 * 0.37).sin() * 3.0;
+                let y = (i as f32 * 0.91).cos() * 3.0;
+                p.add_view_angle_offset([x, y], &view_prefs);
+            }
+        });
+        assert!((q.magnitude() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_blocking_twists() {
+        let r = Twist {
+            axis: TwistAxis(0),
+            direction: TwistDirection(0),
+            layers: LayerMask(1),
+        };
+        let u = Twist {
+            axis: TwistAxis(2),
+            ..r
+        };
+
+        // User input during a blocking animation is rejected.
+        let mut puzzle = PuzzleController::default();
+        puzzle.twist_from_input(r, true).unwrap();
+        assert!(puzzle.is_blocked());
+        assert!(puzzle.twist_from_input(u, false).is_err());
+        assert!(puzzle.twist_from_input(u, true).is_err());
+        assert!(puzzle.undo_from_input(true).is_err());
+        assert!(puzzle.redo_from_input(true).is_err());
+        assert_eq!(puzzle.undo_buffer().len(), 1);
+        assert_eq!(puzzle.twist_anim.queue.len(), 1);
+
+        // Other twists, such as from scrambles, are never dropped.
+        puzzle.twist(u).unwrap();
+        assert_eq!(puzzle.undo_buffer().len(), 2);
+        assert_eq!(puzzle.twist_anim.queue.len(), 2);
+
+        // Once the animation is done, user input is accepted again.
+        puzzle.skip_twist_animations();
+        assert!(!puzzle.is_blocked());
+
+        // Undo, redo, and twists that collapse into an undo also block.
+        puzzle.undo_from_input(true).unwrap();
+        assert!(puzzle.is_blocked());
+        puzzle.skip_twist_animations();
+        puzzle.redo_from_input(true).unwrap();
+        assert!(puzzle.is_blocked());
+        puzzle.skip_twist_animations();
+        puzzle
+            .twist_from_input(puzzle.reverse_twist(u), true)
+            .unwrap();
+        assert_eq!(puzzle.undo_buffer().len(), 1);
+        assert!(puzzle.is_blocked());
+        puzzle.skip_twist_animations();
+
+        // User input during a non-blocking animation is buffered.
+        puzzle.twist_from_input(r, false).unwrap();
+        puzzle.twist_from_input(u, false).unwrap();
+        assert!(!puzzle.is_blocked());
+        assert_eq!(puzzle.undo_buffer().len(), 3);
+        assert_eq!(puzzle.twist_anim.queue.len(), 2);
+    }
+
+    #[test]
+    fn test_axis_overrides() {
+        let r = Twist {
+            axis: TwistAxis(0),
+            direction: TwistDirection(0),
+            layers: LayerMask(1),
+        };
+        let u = Twist {
+            axis: TwistAxis(2),
+            ..r
+        };
+
+        let mut prefs = InteractionPreferences {
+            twist_duration: 1.0,
+            ..Default::default()
+        };
+        let r_name = PuzzleController::default().info(r.axis).name.to_string();
+        prefs.axis_overrides.insert(
+            r_name,
+            crate::preferences::AxisInteractionPreferences {
+                twist_duration: Some(4.0),
+                reverse_direction: true,
+            },
+        );
+
+        let twist_progress_after_100ms = |twist| {
+            let mut puzzle = PuzzleController::default();
+            let twist = puzzle.apply_axis_reversal(twist, &prefs);
+            puzzle.twist_from_input(twist, false).unwrap();
+            puzzle.update_geometry(Duration::from_millis(100), &prefs);
+            assert_eq!(puzzle.current_twist().unwrap().0, twist);
+            puzzle.twist_anim.progress
+        };
+
+        // Only twists on the overridden axis are reversed.
+        let puzzle = PuzzleController::default();
+        assert_eq!(
+            puzzle.apply_axis_reversal(r, &prefs),
+            puzzle.reverse_twist(r),
+        );
+        assert_eq!(puzzle.apply_axis_reversal(u, &prefs), u);
+
+        // Only twists on the overridden axis use its twist duration.
+        assert!((twist_progress_after_100ms(r) - 0.025).abs() < 1e-6);
+        assert!((twist_progress_after_100ms(u) - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_reset_after_scramble() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 3 },
+        ] {
+            let mut state = Puzzle::new(ty);
+            let twists: Vec<_> = state.legal_twists().step_by(7).collect();
+            for twist in twists {
+                state.twist(twist).unwrap();
+            }
+            assert!(!state.is_solved());
+            state.reset();
+            assert!(state.is_solved());
+            assert_eq!(state, Puzzle::new(ty));
+
+            let mut puzzle = PuzzleController::new(ty);
+            puzzle.scramble_full().unwrap();
+            puzzle.twist(state.legal_twists().next().unwrap()).unwrap();
+            assert!(!puzzle.is_solved());
+            puzzle.reset();
+            assert!(puzzle.is_solved());
+            assert!(!puzzle.has_undo());
+            assert!(!puzzle.has_redo());
+            assert!(puzzle.scramble().is_empty());
+            assert_eq!(puzzle.scramble_state(), ScrambleState::None);
+        }
+    }
+
+    #[test]
+    fn test_reset_clears_ui_state() {
+        let modified_puzzle = || {
+            let mut puzzle = PuzzleController::default();
+            puzzle.set_visible_pieces(&bitvec![0; puzzle.pieces().len()]);
+            puzzle.select(Sticker(0));
+            puzzle.scrub_layers(TwistAxis(0), 1);
+            puzzle
+        };
+        let assert_clean = |puzzle: &PuzzleController| {
+            assert!(!puzzle.is_any_piece_hidden());
+            assert!(puzzle.selection().is_empty());
+            assert_eq!(puzzle.layer_scrub(), None);
+        };
+
+        let mut puzzle = modified_puzzle();
+        puzzle.reset();
+        assert_clean(&puzzle);
+
+        // Scrambling resets the puzzle first.
+        let mut puzzle = modified_puzzle();
+        puzzle.scramble_n(5).unwrap();
+        assert_clean(&puzzle);
+    }
+
+    #[test]
+    fn test_reset_confirmation() {
+        let mut puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        assert!(!puzzle.should_confirm_discard(false));
+
+        puzzle.scramble_n(3).unwrap();
+        assert!(puzzle.should_confirm_discard(false));
+        assert!(!puzzle.should_confirm_discard(true));
+
+        puzzle.scramble_full().unwrap();
+        assert!(puzzle.should_confirm_discard(false));
+        assert!(puzzle.should_confirm_discard(true));
+
+        puzzle.mark_saved();
+        assert!(!puzzle.should_confirm_discard(true));
+
+        puzzle.reset();
+        assert!(!puzzle.should_confirm_discard(false));
+    }
+
+    #[test]
+    fn test_hovered_sticker_adjacent_faces() {
+        let mut puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        assert_eq!(puzzle.hovered_sticker_adjacent_faces(), None);
+
+        let face_named = |name| {
+            let i = puzzle.faces().iter().position(|f| f.name == name).unwrap();
+            Face(i as _)
+        };
+        let right = face_named("Right");
+        let expected = ["Up", "Down", "Front", "Back"].map(face_named);
+
+        let sticker = (0..puzzle.stickers().len() as _)
+            .map(Sticker)
+            .find(|&s| puzzle.info(s).color == right)
+            .unwrap();
+        puzzle.update_hovered_sticker([(sticker, ClickTwists::default())]);
+        let adjacent_faces = puzzle.hovered_sticker_adjacent_faces().unwrap();
+        assert_eq!(adjacent_faces.len(), expected.len());
+        for face in expected {
+            assert!(adjacent_faces.contains(&face));
+        }
+    }
+
+    #[test]
+    fn test_layer_scrub() {
+        let mut puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        let axis = TwistAxis(0);
+        assert_eq!(puzzle.layer_scrub(), None);
+
+        let expected_masks = [0b001, 0b010, 0b100, 0b100, 0b010];
+        let steps = [1, 1, 1, 1, -1];
+        for (step, expected) in steps.into_iter().zip(expected_masks) {
+            puzzle.scrub_layers(axis, step);
+            assert_eq!(puzzle.layer_scrub(), Some((axis, LayerMask(expected))));
+
+            let twist = Twist {
+                axis,
+                direction: TwistDirection(0),
+                layers: LayerMask(expected),
+            };
+            for piece in (0..puzzle.pieces().len() as _).map(Piece) {
+                assert_eq!(
+                    puzzle.is_hidden_by_layer_scrub(piece),
+                    !puzzle.displayed().is_piece_affected_by_twist(twist, piece),
+                );
+            }
+        }
+
+        // Scrubbing a different axis starts from its outermost layer.
+        puzzle.scrub_layers(TwistAxis(1), -1);
+        assert_eq!(puzzle.layer_scrub(), Some((TwistAxis(1), LayerMask(0b100))));
+
+        puzzle.stop_layer_scrub();
+        assert_eq!(puzzle.layer_scrub(), None);
+        assert!((0..puzzle.pieces().len() as _)
+            .map(Piece)
+            .all(|piece| !puzzle.is_hidden_by_layer_scrub(piece)));
+    }
+
+    #[test]
+    fn test_smart_realign() {
+        let prefs = |smart_realign, smart_realign_threshold| InteractionPreferences {
+            smart_realign,
+            smart_realign_threshold,
+            ..Default::default()
+        };
+        let realigns_to_new_orientation = |angle: f32, prefs: InteractionPreferences| {
+            let mut puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+            puzzle.view_angle.current = Quaternion::from_angle_y(Deg(angle));
+            puzzle.update_transient_rotation(&prefs);
+            puzzle.view_angle.transient_rotation.is_some()
+        };
+
+        // Less than halfway to a 90-degree rotation snaps back to 0 degrees.
+        assert!(!realigns_to_new_orientation(40.0, prefs(true, 180.0)));
+        // More than halfway snaps to 90 degrees.
+        assert!(realigns_to_new_orientation(50.0, prefs(true, 180.0)));
+        assert!(realigns_to_new_orientation(80.0, prefs(true, 30.0)));
+        // Too far from 90 degrees snaps back to 0 degrees.
+        assert!(!realigns_to_new_orientation(50.0, prefs(true, 30.0)));
+        // Without smart realign, always snap back to 0 degrees.
+        assert!(!realigns_to_new_orientation(50.0, prefs(false, 180.0)));
+        assert!(!realigns_to_new_orientation(80.0, prefs(false, 180.0)));
+
+        // Returns whether the puzzle realigns after dragging it 80 degrees and
+        // then doing something.
+        fn realigns_after(
+            prefs: &InteractionPreferences,
+            f: impl FnOnce(&mut PuzzleController),
+        ) -> bool {
+            let mut puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+            puzzle.freeze_view_angle_offset();
+            puzzle.view_angle.current = Quaternion::from_angle_y(Deg(80.0));
+            puzzle.update_transient_rotation(prefs);
+            f(&mut puzzle);
+            !puzzle.view_angle.is_frozen
+        }
+        for (realign_on_release, realign_on_keypress) in
+            [(false, false), (true, false), (false, true), (true, true)]
+        {
+            let prefs = InteractionPreferences {
+                realign_on_release,
+                realign_on_keypress,
+                ..prefs(true, 180.0)
+            };
+            // Releasing the mouse after dragging.
+            let release = |p: &mut PuzzleController| p.release_view_angle_offset(&prefs);
+            assert_eq!(realigns_after(&prefs, release), realign_on_release);
+            // Gripping an axis from the keyboard.
+            let keypress = |p: &mut PuzzleController| {
+                p.set_grip(Grip::with_axis(TwistAxis(0)), &prefs);
+            };
+            assert_eq!(realigns_after(&prefs, keypress), realign_on_keypress);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Export of puzzle geometry to 3D model formats.
+
+use itertools::Itertools;
+use std::fmt::Write;
+
+use super::*;
+
+/// Wavefront OBJ model of a puzzle, along with its material library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjModel {
+    /// Contents of the `.obj` file.
+    pub obj: String,
+    /// Contents of the `.mtl` file.
+    pub mtl: String,
+}
+impl ObjModel {
+    /// Constructs an OBJ model of the visible surface of a puzzle, with one
+    /// group per piece and per sticker and one material per face.
+    ///
+    /// Geometry is taken after the view transform and 4D projection from
+    /// `params`, but before 3D perspective projection. Each sticker is
+    /// exported once facing outward, regardless of whether `params` shows
+    /// frontfaces or backfaces. `mtl_file_name` is the name that the `.obj`
+    /// file uses to refer to the `.mtl` file.
+    pub fn new(
+        puzzle: &Puzzle,
+        params: StickerGeometryParams,
+        face_colors: &[egui::Color32],
+        mtl_file_name: &str,
+    ) -> Self {
+        let params = StickerGeometryParams {
+            show_frontfaces: true,
+            show_backfaces: false,
+            ..params
+        };
+
+        let mut obj = String::new();
+        let mut mtl = String::new();
+
+        writeln!(obj, "# {}", puzzle.name()).unwrap();
+        writeln!(obj, "mtllib {mtl_file_name}").unwrap();
+
+        for (face, &color) in puzzle.faces().iter().zip(face_colors) {
+            let [r, g, b, _] = egui::Rgba::from(color).to_array();
+            writeln!(mtl, "newmtl {}", face.symbol).unwrap();
+            writeln!(mtl, "Kd {r} {g} {b}").unwrap();
+        }
+
+        // OBJ vertex indices start at 1.
+        let mut vertex_count = 0;
+        for sticker in (0..puzzle.stickers().len() as _).map(Sticker) {
+            let Some(geom) = puzzle.sticker_geometry(sticker, params) else {
+                continue;
+            };
+            let info = puzzle.info(sticker);
+
+            writeln!(obj, "g piece_{} sticker_{}", info.piece.0, sticker.0).unwrap();
+            writeln!(obj, "usemtl {}", puzzle.info(info.color).symbol).unwrap();
+            for v in &geom.verts {
+                writeln!(obj, "v {} {} {}", v.x, v.y, v.z).unwrap();
+            }
+            for indices in &geom.polygon_indices {
+                let indices = indices.iter().map(|&i| vertex_count + i as usize + 1);
+                writeln!(obj, "f {}", indices.map(|i| i.to_string()).join(" ")).unwrap();
+            }
+            vertex_count += geom.verts.len();
+        }
+
+        Self { obj, mtl }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{One, Quaternion};
+
+    use crate::preferences::ViewPreferences;
+
+    use super::*;
+
+    #[test]
+    fn test_rubiks_3d_obj_export() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        // The export ignores which faces are shown.
+        let view_prefs = ViewPreferences {
+            show_frontfaces: false,
+            show_backfaces: true,
+            ..Default::default()
+        };
+        let params = StickerGeometryParams::new(&view_prefs, ty, None, Quaternion::one());
+        let colors = vec![egui::Color32::RED; ty.faces().len()];
+        let model = ObjModel::new(&Puzzle::new(ty), params, &colors, "cube.mtl");
+
+        let lines_starting_with =
+            |s: &str, prefix: &str| s.lines().filter(|l| l.starts_with(prefix)).count();
+        assert_eq!(lines_starting_with(&model.obj, "v "), 54 * 4);
+        assert_eq!(lines_starting_with(&model.obj, "f "), 54);
+        assert_eq!(lines_starting_with(&model.obj, "g "), 54);
+        assert_eq!(lines_starting_with(&model.mtl, "newmtl "), 6);
+        assert!(model.obj.contains("mtllib cube.mtl"));
+
+        // Every face refers to a valid vertex.
+        for line in model.obj.lines().filter(|l| l.starts_with("f ")) {
+            for i in line.split(' ').skip(1) {
+                let i: usize = i.parse().unwrap();
+                assert!((1..=54 * 4).contains(&i));
+            }
+        }
+    }
+}
@@ -37,12 +37,19 @@ pub struct StickerGeometryParams {
     /// 3D FOV, in degrees.
     pub fov_3d: f32,
 
+    /// Offset of the 4D camera along the X, Y, and Z axes, as a fraction of
+    /// the distance from the camera to the puzzle center.
+    pub camera_4d_offset: Vector3<f32>,
+
     /// Factor of how much the W coordinate affects the XYZ coordinates. This is
     /// computed from the 4D FOV.
     pub w_factor_4d: f32,
     /// Factor of how much the Z coordinate affects the XY coordinates. This is
-    /// computed from the 3D FOV.
+    /// computed from the 3D FOV, and is zero for an orthographic projection.
     pub w_factor_3d: f32,
+    /// Horizontal offset of the 3D camera, for rendering one eye of a stereo
+    /// pair.
+    pub eye_offset: f32,
 
     /// Animated twist and animation progress.
     pub twist_animation: Option<(Twist, f32)>,
@@ -51,8 +58,10 @@ pub struct StickerGeometryParams {
 
     /// Ambient lighting amount (0.0..=1.0).
     pub ambient_light: f32,
-    /// Light vector (manitude of 0.0..=1.0).
+    /// Light vector (magnitude of 0.0..=1.0).
     pub light_vector: Vector3<f32>,
+    /// Fill light vector (magnitude of 0.0..=1.0).
+    pub fill_light_vector: Vector3<f32>,
 
     /// Whether to show frontfaces.
     pub show_frontfaces: bool,
@@ -83,6 +92,13 @@ impl StickerGeometryParams {
             * Vector3::unit_z()
             * view_prefs.light_directional
             * 0.5;
+        let fill_light_vector = Matrix3::from_angle_y(Deg(view_prefs.fill_light_yaw))
+            * Matrix3::from_angle_x(Deg(-view_prefs.fill_light_pitch))
+            * Vector3::unit_z()
+            * view_prefs.fill_light
+            * 0.5;
+
+        let face_visibility = view_prefs.face_visibility(puzzle_type);
 
         let face_spacing = view_prefs.face_spacing;
         let sticker_spacing = if puzzle_type.layer_count() > 1 {
@@ -106,17 +122,24 @@ impl StickerGeometryParams {
 
             fov_4d: view_prefs.fov_4d,
             fov_3d: view_prefs.fov_3d,
+            camera_4d_offset: view_prefs.camera_4d_offset.into(),
             w_factor_4d: (view_prefs.fov_4d.to_radians() / 2.0).tan(),
-            w_factor_3d: (view_prefs.fov_3d.to_radians() / 2.0).tan(),
+            w_factor_3d: if view_prefs.orthographic_3d {
+                0.0
+            } else {
+                (view_prefs.fov_3d.to_radians() / 2.0).tan()
+            },
+            eye_offset: 0.0,
 
             twist_animation,
             view_transform,
 
             ambient_light,
             light_vector,
+            fill_light_vector,
 
-            show_frontfaces: view_prefs.show_frontfaces,
-            show_backfaces: view_prefs.show_backfaces,
+            show_frontfaces: face_visibility.show_frontfaces,
+            show_backfaces: face_visibility.show_backfaces,
             clip_4d: view_prefs.clip_4d,
         };
 
@@ -139,7 +162,18 @@ impl StickerGeometryParams {
             return None;
         }
 
-        Some(Point3::from_vec(point.truncate()) / divisor)
+        // Points converge toward the point directly in front of the camera,
+        // which is the origin unless the camera is offset.
+        let vanishing_point = Point3::from_vec(self.camera_4d_offset * camera_w);
+        Some(vanishing_point + (point.truncate() - vanishing_point.to_vec()) / divisor)
+    }
+
+    /// Returns the illumination of a polygon with the given unit normal vector.
+    pub fn illumination(self, normal: Vector3<f32>) -> f32 {
+        // The fill light only brightens polygons facing toward it, so that it
+        // doesn't darken the side lit by the main light.
+        let fill = normal.dot(self.fill_light_vector).max(0.0);
+        self.ambient_light + normal.dot(self.light_vector) + fill
     }
 
     /// Projects a 3D point according to the perspective projection.
@@ -154,6 +188,10 @@ impl StickerGeometryParams {
         // This Desmos graph shows how this divisor varies with respect to Z
         // (shown along the X axis) and the FOV (controlled by a slider):
         // https://www.desmos.com/calculator/ocztouh1h0
+        //
+        // For an orthographic projection, `w_factor_3d` is zero so the divisor
+        // is always 1. The Z coordinate is preserved either way, so depth
+        // sorting still works.
         let divisor = 1.0 + (self.fov_3d.signum() - point.z) * self.w_factor_3d;
 
         // Clip geometry that is behind the 3D camera.
@@ -166,7 +204,12 @@ impl StickerGeometryParams {
         // coordinate. If you want to implement this dolly zoom effect yourself,
         // though, you'll probably need to consider that.
 
-        Some(point / divisor)
+        // Shift the camera horizontally for stereo rendering. Like the FOV,
+        // this keeps points where the divisor is 1 fixed, so that the puzzle
+        // appears at the same depth as the screen.
+        let mut ret = point / divisor;
+        ret.x += self.eye_offset * (1.0 - 1.0 / divisor);
+        Some(ret)
     }
 }
 
@@ -236,6 +279,9 @@ pub(crate) struct ProjectedStickerGeometry {
 
     pub front_polygons: Box<[Polygon]>,
     pub back_polygons: Box<[Polygon]>,
+
+    /// Whether this is a translucent preview of the sticker after a twist.
+    pub is_preview: bool,
 }
 impl ProjectedStickerGeometry {
     pub(crate) fn twists_for_point(&self, point: Point2<f32>) -> Option<ClickTwists> {
@@ -336,6 +382,9 @@ pub(crate) fn polygon_from_indices(
     }
 }
 
+/// Returns the (unnormalized) normal vector of a polygon. The normal points
+/// toward the camera (+Z) when the polygon is front-facing, so puzzles must
+/// wind their front polygons such that this points outward from the puzzle.
 pub(crate) fn polygon_normal_from_indices(verts: &[Point3<f32>], indices: &[u16]) -> Vector3<f32> {
     let a = verts[indices[0] as usize];
     let b = verts[indices[1] as usize];
@@ -584,3 +633,125 @@ impl PointRelativeToLine {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_off_axis_4d_projection() {
+        let ty = PuzzleTypeEnum::Rubiks4D { layer_count: 3 };
+        let mut view_prefs = ViewPreferences {
+            fov_4d: 30.0,
+            ..Default::default()
+        };
+        let centered = StickerGeometryParams::new(&view_prefs, ty, None, Quaternion::one());
+        view_prefs.camera_4d_offset = [0.5, 0.0, 0.0];
+        let offset = StickerGeometryParams::new(&view_prefs, ty, None, Quaternion::one());
+        let camera_w = offset.face_scale;
+
+        // Points nearest to the camera are unaffected.
+        let near = vec4(0.2, 0.3, -0.1, camera_w);
+        assert_eq!(centered.project_4d(near), offset.project_4d(near));
+
+        // Points further away are shifted toward the camera offset.
+        let far = vec4(0.2, 0.3, -0.1, -camera_w);
+        let a = centered.project_4d(far).unwrap();
+        let b = offset.project_4d(far).unwrap();
+        assert!(b.x > a.x);
+        assert!((b.y - a.y).abs() < EPSILON);
+        assert!((b.z - a.z).abs() < EPSILON);
+
+        // Clipping behind the camera is unaffected by the offset.
+        let behind = vec4(0.0, 0.0, 0.0, camera_w * 100.0);
+        assert_eq!(centered.project_4d(behind), None);
+        assert_eq!(offset.project_4d(behind), None);
+    }
+
+    #[test]
+    fn test_orthographic_3d_projection() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let view_prefs = ViewPreferences {
+            fov_3d: 60.0,
+            orthographic_3d: true,
+            ..Default::default()
+        };
+        let params = StickerGeometryParams::new(&view_prefs, ty, None, Quaternion::one());
+
+        // Points at different depths are not scaled, and depth is preserved.
+        for z in [-1.0, 0.0, 1.0] {
+            let p = point3(0.3, -0.4, z);
+            assert_eq!(params.project_3d(p), Some(p));
+        }
+
+        // The perspective projection does scale points by depth.
+        let params = StickerGeometryParams::new(
+            &ViewPreferences {
+                orthographic_3d: false,
+                ..view_prefs
+            },
+            ty,
+            None,
+            Quaternion::one(),
+        );
+        let near = params.project_3d(point3(0.3, -0.4, 1.0)).unwrap();
+        let far = params.project_3d(point3(0.3, -0.4, -1.0)).unwrap();
+        assert!(far.x < near.x);
+    }
+
+    #[test]
+    fn test_stereo_projection() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mono =
+            StickerGeometryParams::new(&ViewPreferences::default(), ty, None, Quaternion::one());
+        let right_eye = StickerGeometryParams {
+            eye_offset: 0.2,
+            ..mono
+        };
+
+        // Points at the front of the puzzle are at screen depth, so they are
+        // unaffected by the eye offset.
+        let front = point3(0.3, -0.4, 1.0);
+        assert_eq!(right_eye.project_3d(front), mono.project_3d(front));
+
+        // Points further away shift toward the eye.
+        let back = point3(0.3, -0.4, -1.0);
+        let a = mono.project_3d(back).unwrap();
+        let b = right_eye.project_3d(back).unwrap();
+        assert!(b.x > a.x);
+        assert_eq!((a.y, a.z), (b.y, b.z));
+    }
+
+    #[test]
+    fn test_fill_light() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut view_prefs = ViewPreferences {
+            light_directional: 0.7,
+            light_ambient: 0.4,
+            light_pitch: 20.0,
+            light_yaw: -40.0,
+            ..Default::default()
+        };
+        let normals = [
+            Vector3::unit_x(),
+            -Vector3::unit_y(),
+            vec3(1.0, 2.0, -3.0).normalize(),
+        ];
+
+        // With no fill light, shading is the same as with only the main light.
+        let params = StickerGeometryParams::new(&view_prefs, ty, None, Quaternion::one());
+        for n in normals {
+            let expected = params.ambient_light + n.dot(params.light_vector);
+            assert!((params.illumination(n) - expected).abs() < EPSILON);
+        }
+
+        // The fill light brightens polygons facing it and never darkens any.
+        view_prefs.fill_light = 0.5;
+        view_prefs.fill_light_yaw = 90.0;
+        let filled = StickerGeometryParams::new(&view_prefs, ty, None, Quaternion::one());
+        assert!(filled.illumination(Vector3::unit_x()) > params.illumination(Vector3::unit_x()));
+        for n in normals {
+            assert!(filled.illumination(n) >= params.illumination(n));
+        }
+    }
+}
@@ -4,17 +4,23 @@
 mod common;
 
 pub mod controller;
+pub mod export;
 pub mod geometry;
 pub mod notation;
 pub mod rubiks_3d;
 pub mod rubiks_4d;
+pub mod stats;
+pub mod twist_table;
 
 pub use common::*;
 pub use controller::*;
+pub use export::ObjModel;
 pub use geometry::*;
 pub use notation::*;
-pub use rubiks_3d::Rubiks3D;
+pub use rubiks_3d::{HintSearch, Rubiks3D};
 pub use rubiks_4d::Rubiks4D;
+pub use stats::PuzzleStats;
+pub use twist_table::TwistTable;
 
 pub mod traits {
     pub use super::{PuzzleInfo, PuzzleState, PuzzleType};
@@ -22,7 +28,9 @@ pub mod traits {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
+    use std::collections::{HashMap, HashSet};
+    use std::fmt::Debug;
+    use std::hash::Hash;
 
     use super::*;
 
@@ -48,6 +56,31 @@ mod tests {
         }
     }
 
+    /// Test that every piece has at least one sticker and that no two pieces
+    /// occupy the same location.
+    pub(super) fn test_pieces_are_distinct<L: Eq + Hash + Debug>(
+        p: &impl PuzzleType,
+        piece_location: impl Fn(Piece) -> L,
+    ) {
+        let mut seen = HashMap::new();
+        for piece in (0..p.pieces().len() as _).map(Piece) {
+            assert!(
+                !p.info(piece).stickers.is_empty(),
+                "{:?} of {} has no stickers",
+                piece,
+                p.name(),
+            );
+            if let Some(other) = seen.insert(piece_location(piece), piece) {
+                panic!(
+                    "{:?} and {:?} of {} occupy the same location",
+                    other,
+                    piece,
+                    p.name(),
+                );
+            }
+        }
+    }
+
     /// Test that every canonical twist can be losslessly serialized/deserialized.
     pub(super) fn test_twist_serialization(p: &impl PuzzleType) {
         let mut seen = HashSet::new();
@@ -92,6 +125,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_seeded_scramble() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 3 },
+        ] {
+            let mut a = Puzzle::new(ty);
+            let mut b = Puzzle::new(ty);
+            let twists = a.scramble(42);
+            assert_eq!(twists, b.scramble(42));
+            assert_eq!(a, b);
+            assert_eq!(twists.len(), ty.scramble_moves_count());
+            assert_ne!(twists, Puzzle::new(ty).scramble(43));
+
+            for pair in twists.windows(2) {
+                assert_ne!(ty.canonicalize_twist(ty.reverse_twist(pair[1])), pair[0]);
+            }
+
+            // Undoing the scramble solves the puzzle.
+            assert!(!a.is_solved());
+            for &twist in twists.iter().rev() {
+                a.twist(ty.reverse_twist(twist)).unwrap();
+            }
+            assert!(a.is_solved());
+        }
+    }
+
     fn iter_all_twists(p: &impl PuzzleType) -> impl Iterator<Item = Twist> {
         itertools::iproduct!(
             (0..p.twist_axes().len() as _).map(TwistAxis),
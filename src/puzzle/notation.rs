@@ -167,6 +167,76 @@ impl NotationScheme {
         }
     }
 
+    /// Parses a whitespace-separated sequence of twists. A group of twists in
+    /// parentheses may be followed by a repetition count, such as
+    /// `(R U R' U')6`.
+    pub fn parse_twists(&self, s: &str) -> Result<Vec<Twist>, String> {
+        const MAX_TWIST_COUNT: usize = 10_000;
+
+        // Each element is the list of twists in an open group; the first one
+        // is the top level.
+        let mut groups: Vec<Vec<Twist>> = vec![vec![]];
+        let mut remaining = s.trim_start();
+        while !remaining.is_empty() {
+            if let Some(rest) = remaining.strip_prefix('(') {
+                groups.push(vec![]);
+                remaining = rest;
+            } else if let Some(rest) = remaining.strip_prefix(')') {
+                let digits_len = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                let count = match &rest[..digits_len] {
+                    "" => 1,
+                    digits => digits
+                        .parse::<usize>()
+                        .map_err(|_| format!("invalid repetition count {digits:?}"))?,
+                };
+                remaining = &rest[digits_len..];
+
+                if groups.len() < 2 {
+                    return Err("unmatched ')'".to_string());
+                }
+                let group = groups.pop().unwrap();
+                let parent = groups.last_mut().unwrap();
+                match group.len().checked_mul(count) {
+                    Some(n) if parent.len() + n <= MAX_TWIST_COUNT => {
+                        parent.extend(group.iter().cycle().take(n));
+                    }
+                    _ => return Err(format!("cannot parse more than {MAX_TWIST_COUNT} twists")),
+                }
+            } else {
+                // A twist ends at whitespace or a parenthesis, except inside
+                // a layer mask such as `{1, 3}`.
+                let mut in_braces = false;
+                let len = remaining
+                    .find(|c: char| match c {
+                        '{' | '}' => {
+                            in_braces = c == '{';
+                            false
+                        }
+                        _ => !in_braces && (c.is_whitespace() || c == '(' || c == ')'),
+                    })
+                    .unwrap_or(remaining.len());
+                let token = &remaining[..len];
+                let twist = self
+                    .parse_twist(token)
+                    .map_err(|e| format!("{e} {token:?}"))?;
+                let current = groups.last_mut().unwrap();
+                if current.len() >= MAX_TWIST_COUNT {
+                    return Err(format!("cannot parse more than {MAX_TWIST_COUNT} twists"));
+                }
+                current.push(twist);
+                remaining = &remaining[len..];
+            }
+            remaining = remaining.trim_start();
+        }
+
+        if groups.len() > 1 {
+            return Err("unmatched '('".to_string());
+        }
+        Ok(groups.pop().unwrap())
+    }
+
     fn parse_twist_direction(
         &self,
         axis: TwistAxis,
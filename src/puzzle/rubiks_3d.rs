@@ -8,6 +8,7 @@ use smallvec::smallvec;
 use std::collections::HashMap;
 use std::ops::{Index, IndexMut, RangeInclusive};
 use std::sync::Mutex;
+use std::task::Poll;
 use strum::IntoEnumIterator;
 
 use super::*;
@@ -17,6 +18,9 @@ pub const MIN_LAYER_COUNT: u8 = 1;
 pub const MAX_LAYER_COUNT: u8 = 9;
 pub const LAYER_COUNT_RANGE: RangeInclusive<u8> = MIN_LAYER_COUNT..=MAX_LAYER_COUNT;
 
+/// Maximum number of twists to search when looking for a hint.
+const HINT_MAX_DEPTH: usize = 4;
+
 pub(super) fn deserialize_layer_count<'de, D>(deserializer: D) -> Result<u8, D::Error>
 where
     D: Deserializer<'de>,
@@ -154,9 +158,7 @@ fn puzzle_description(layer_count: u8) -> &'static Rubiks3DDescription {
             aliases,
         };
 
-        // It's not like we'll ever clear the cache anyway, so just leak it
-        // and let us have the 'static lifetimes.
-        Box::leak(Box::new(Rubiks3DDescription {
+        let desc = Rubiks3DDescription {
             name: format!("{0}x{0}x{0}", layer_count),
 
             layer_count,
@@ -173,7 +175,12 @@ fn puzzle_description(layer_count: u8) -> &'static Rubiks3DDescription {
             notation,
 
             piece_locations,
-        }))
+        };
+        debug_assert_eq!(desc.check_sticker_pieces(), Ok(()));
+
+        // It's not like we'll ever clear the cache anyway, so just leak it
+        // and let us have the 'static lifetimes.
+        Box::leak(Box::new(desc))
     })
 }
 
@@ -255,6 +262,16 @@ impl PuzzleType for Rubiks3DDescription {
             CW180 | CCW180 => 2,
         }
     }
+    fn twist_axis_normal(&self, twist_axis: TwistAxis) -> Vec<f32> {
+        <[f32; 3]>::from(FaceEnum::from(twist_axis).vector()).to_vec()
+    }
+    fn twist_transform(&self, twist_axis: TwistAxis, direction: TwistDirection) -> Vec<Vec<f32>> {
+        let rot = FaceEnum::from(twist_axis).twist_rotation(direction.into());
+        let matrix = Matrix3::from(rot);
+        (0..3)
+            .map(|i| <[f32; 3]>::from(matrix.row(i)).to_vec())
+            .collect()
+    }
 
     fn make_recenter_twist(&self, axis: TwistAxis) -> Result<Twist, String> {
         use FaceEnum::*;
@@ -358,11 +375,15 @@ impl IndexMut<Piece> for Rubiks3D {
 }
 impl PuzzleState for Rubiks3D {
     fn twist(&mut self, twist: Twist) -> Result<(), &'static str> {
+        self.can_twist(twist)?;
         for piece in self.pieces_affected_by_twist(twist) {
             self[piece] = self[piece].twist(twist.axis.into(), twist.direction.into());
         }
         Ok(())
     }
+    fn reset(&mut self) {
+        self.piece_states.fill(PieceState::default());
+    }
     fn layer_from_twist_axis(&self, twist_axis: TwistAxis, piece: Piece) -> u8 {
         let face: FaceEnum = twist_axis.into();
         let face_coord = match face.sign() {
@@ -507,6 +528,42 @@ impl PuzzleState for Rubiks3D {
         }
         true
     }
+
+    fn snapshot(&self) -> PuzzleSnapshot {
+        PuzzleSnapshot {
+            ty: self.ty(),
+            piece_orientations: self
+                .piece_states
+                .iter()
+                .map(|state| state.0.iter().map(|&face| face as u8).collect())
+                .collect(),
+        }
+    }
+    fn load_snapshot(&mut self, snapshot: &PuzzleSnapshot) -> Result<(), &'static str> {
+        if snapshot.ty != self.ty() {
+            return Err("snapshot is for a different puzzle type");
+        }
+        if snapshot.piece_orientations.len() != self.piece_states.len() {
+            return Err("snapshot has wrong number of pieces");
+        }
+        let new_state = Self {
+            desc: self.desc,
+            piece_states: snapshot
+                .piece_orientations
+                .iter()
+                .map(|faces| PieceState::from_face_ids(faces))
+                .collect::<Option<_>>()
+                .ok_or("snapshot has invalid piece orientation")?,
+        };
+        let locations_are_unique = (0..new_state.piece_states.len())
+            .map(|i| new_state.piece_location(Piece(i as _)))
+            .all_unique();
+        if !locations_are_unique {
+            return Err("snapshot has multiple pieces in the same location");
+        }
+        *self = new_state;
+        Ok(())
+    }
 }
 #[delegate_to_methods]
 #[delegate(PuzzleType, target_ref = "desc")]
@@ -565,6 +622,193 @@ impl Rubiks3D {
     fn piece_center_coordinate(&self, x: u8, p: StickerGeometryParams) -> f32 {
         (2.0 * x as f32 - (self.layer_count() - 1) as f32) * p.sticker_grid_scale
     }
+
+    /// Returns a short sequence of outer-layer twists that makes progress
+    /// toward solving a 3x3x3 using a simple layer-by-layer method: first the
+    /// edges and then corners of the D layer, then the middle layer edges,
+    /// then the edges and then corners of the U layer.
+    ///
+    /// Returns `None` if the puzzle is not a 3x3x3, is already solved, or if
+    /// no progress can be made within a few twists. The search only tries
+    /// sequences of up to `HINT_MAX_DEPTH` single-layer twists, so it usually
+    /// finds nothing in the last layer, where progress requires longer
+    /// algorithms.
+    ///
+    /// This runs the whole search at once; use [`Rubiks3D::hint_search()`] to
+    /// spread it out over time instead.
+    pub fn solve_hint(&self) -> Option<Vec<Twist>> {
+        let mut search = self.hint_search()?;
+        loop {
+            if let Poll::Ready(hint) = search.step() {
+                return hint;
+            }
+        }
+    }
+    /// Starts a search for the hint returned by [`Rubiks3D::solve_hint()`], or
+    /// returns `None` if the puzzle is not a 3x3x3 or is already solved.
+    pub fn hint_search(&self) -> Option<HintSearch> {
+        if self.layer_count() != 3 || self.is_solved() {
+            return None;
+        }
+
+        // Outer-layer twists never move the centers, so the center colors only
+        // need to be found once for the whole search.
+        let stages = self.hint_stages();
+        let center_faces = self.hint_center_faces();
+        let moves = self
+            .legal_twists()
+            // `CCW180` is the same as `CW180`.
+            .filter(|twist| {
+                twist.layers == LayerMask(1)
+                    && TwistDirectionEnum::from(twist.direction) != TwistDirectionEnum::CCW180
+            })
+            .collect();
+        let initial_score = self.hint_score(&stages, &center_faces);
+
+        Some(HintSearch {
+            puzzle: self.clone(),
+            stages,
+            center_faces,
+            moves,
+            initial_score,
+            depth: 1,
+            next_first_move: 0,
+            best: None,
+        })
+    }
+    /// Returns the pieces to solve in each stage of a layer-by-layer solve.
+    fn hint_stages(&self) -> [Vec<Piece>; 5] {
+        let mut stages: [Vec<Piece>; 5] = Default::default();
+        for (i, &[x, y, z]) in self.desc.piece_locations.iter().enumerate() {
+            // 0 = corner, 1 = edge, 2 = center
+            let centered_coords = [x, y, z].iter().filter(|&&c| c == 1).count();
+            let stage = match (y, centered_coords) {
+                (0, 1) => 0,
+                (0, 0) => 1,
+                (1, 1) => 2,
+                (2, 1) => 3,
+                (2, 0) => 4,
+                _ => continue,
+            };
+            stages[stage].push(Piece(i as _));
+        }
+        stages
+    }
+    /// Returns the face that the center of each color is facing, indexed by
+    /// color.
+    fn hint_center_faces(&self) -> Vec<FaceEnum> {
+        let mut center_faces = vec![FaceEnum::default(); self.faces().len()];
+        for (i, info) in self.stickers().iter().enumerate() {
+            if self.info(info.piece).stickers.len() == 1 {
+                center_faces[info.color.0 as usize] = self.sticker_face(Sticker(i as _));
+            }
+        }
+        center_faces
+    }
+    /// Returns the number of fully solved stages, followed by the number of
+    /// solved pieces in the next stage. Pieces are considered solved relative
+    /// to the centers, so that whole-puzzle rotations don't matter.
+    fn hint_score(&self, stages: &[Vec<Piece>], center_faces: &[FaceEnum]) -> (usize, usize) {
+        let is_solved = |piece: Piece| {
+            self.info(piece).stickers.iter().all(|&sticker| {
+                center_faces[self.info(sticker).color.0 as usize] == self.sticker_face(sticker)
+            })
+        };
+
+        let mut solved_stages = 0;
+        for stage in stages {
+            let solved_pieces = stage.iter().filter(|&&piece| is_solved(piece)).count();
+            if solved_pieces < stage.len() {
+                return (solved_stages, solved_pieces);
+            }
+            solved_stages += 1;
+        }
+        (solved_stages, 0)
+    }
+}
+
+/// Incremental search for a hint on a 3x3x3, started by
+/// [`Rubiks3D::hint_search()`].
+#[derive(Debug, Clone)]
+pub struct HintSearch {
+    /// Scratch puzzle that is twisted during the search and always restored
+    /// afterward.
+    puzzle: Rubiks3D,
+    stages: [Vec<Piece>; 5],
+    center_faces: Vec<FaceEnum>,
+    moves: Vec<Twist>,
+    initial_score: (usize, usize),
+
+    /// Length of the sequences currently being searched.
+    depth: usize,
+    /// Index into `moves` of the first twist of the next branch to search.
+    next_first_move: usize,
+    /// Best-scoring sequence of length `depth` found so far.
+    best: Option<((usize, usize), Vec<Twist>)>,
+}
+impl HintSearch {
+    /// Searches all sequences that start with the next first twist, and
+    /// returns the hint once the search is complete.
+    pub fn step(&mut self) -> Poll<Option<Vec<Twist>>> {
+        let first_move = self.moves[self.next_first_move];
+        let mut sequence = vec![first_move];
+        self.puzzle.twist(first_move).expect("invalid hint twist");
+        self.search(self.depth - 1, &mut sequence);
+        self.puzzle
+            .twist(self.puzzle.reverse_twist(first_move))
+            .expect("invalid hint twist");
+
+        self.next_first_move += 1;
+        if self.next_first_move < self.moves.len() {
+            return Poll::Pending;
+        }
+
+        // Every sequence of this length has been searched.
+        let initial_score = self.initial_score;
+        if let Some((_, twists)) = self.best.take().filter(|(score, _)| *score > initial_score) {
+            return Poll::Ready(Some(twists));
+        }
+        if self.depth >= HINT_MAX_DEPTH {
+            return Poll::Ready(None);
+        }
+        self.depth += 1;
+        self.next_first_move = 0;
+        Poll::Pending
+    }
+
+    /// Searches all sequences of exactly `depth` more twists, recording the
+    /// best-scoring one in `self.best`.
+    fn search(&mut self, depth: usize, sequence: &mut Vec<Twist>) {
+        if depth == 0 {
+            let score = self.puzzle.hint_score(&self.stages, &self.center_faces);
+            if self
+                .best
+                .as_ref()
+                .map_or(true, |(best_score, _)| score > *best_score)
+            {
+                self.best = Some((score, sequence.clone()));
+            }
+            return;
+        }
+
+        for i in 0..self.moves.len() {
+            let twist = self.moves[i];
+            // Don't twist the same axis twice in a row.
+            if sequence
+                .last()
+                .map_or(false, |last| last.axis == twist.axis)
+            {
+                continue;
+            }
+            self.puzzle.twist(twist).expect("invalid hint twist");
+            sequence.push(twist);
+            self.search(depth - 1, sequence);
+            sequence.pop();
+            self.puzzle
+                .twist(self.puzzle.reverse_twist(twist))
+                .expect("invalid hint twist");
+        }
+    }
 }
 
 /// The facing directions of the X+, Y+, and Z+ stickers on this piece (assuming
@@ -591,6 +835,19 @@ impl IndexMut<Axis> for PieceState {
     }
 }
 impl PieceState {
+    /// Constructs a piece state from face IDs, returning `None` if they do not
+    /// form a valid (non-mirrored) orientation.
+    fn from_face_ids(face_ids: &[u8]) -> Option<Self> {
+        let faces: [FaceEnum; 3] = face_ids
+            .iter()
+            .map(|&i| FaceEnum::iter().nth(i as usize))
+            .collect::<Option<Vec<_>>>()?
+            .try_into()
+            .ok()?;
+        is_proper_orientation(&faces.map(|face| (face.axis() as u8, face.sign())))
+            .then_some(Self(faces))
+    }
+
     #[must_use]
     fn rotate(mut self, from: Axis, to: Axis) -> Self {
         let diff = (from as u8 ^ to as u8) << 1;
@@ -954,6 +1211,215 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rubiks_3d_twist_notation_round_trip() {
+        for layer_count in 1..=4 {
+            let ty = PuzzleTypeEnum::Rubiks3D { layer_count };
+            for axis in (0..ty.twist_axes().len() as _).map(TwistAxis) {
+                for direction in (0..ty.twist_directions().len() as _).map(TwistDirection) {
+                    for layers in (1..=ty.all_layers().0).map(LayerMask) {
+                        let twist = Twist {
+                            axis,
+                            direction,
+                            layers,
+                        };
+                        let s = twist.to_notation_string(ty);
+                        assert_eq!(
+                            Twist::parse(ty, &s),
+                            Ok(ty.canonicalize_twist(twist)),
+                            "round trip failed for {s:?}",
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rubiks_3d_parse_twist_sequence() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let notation = ty.notation_scheme();
+        let parse_each = |s: &str| {
+            s.split(' ')
+                .map(|t| notation.parse_twist(t).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(notation.parse_twists(""), Ok(vec![]));
+        assert_eq!(
+            notation.parse_twists("  R U'\tRw2 {1,3}L x  M' "),
+            Ok(parse_each("R U' Rw2 {1,3}L x M'")),
+        );
+        assert_eq!(
+            notation.parse_twists("F (R U R' U')2 ({1, 3}L)"),
+            Ok(parse_each("F R U R' U' R U R' U' {1,3}L")),
+        );
+        assert_eq!(
+            notation.parse_twists("((R U)2 D)2"),
+            Ok(parse_each("R U R U D R U R U D")),
+        );
+        assert_eq!(notation.parse_twists("(R)0"), Ok(vec![]));
+
+        assert!(notation
+            .parse_twists("R Q U")
+            .unwrap_err()
+            .contains("\"Q\""));
+        assert!(notation.parse_twists("(R U").is_err());
+        assert!(notation.parse_twists("R U)").is_err());
+        assert!(notation.parse_twists("(R)99999999999").is_err());
+    }
+
+    #[test]
+    fn test_rubiks_3d_legal_twists() {
+        for layer_count in 1..=4 {
+            let p = Rubiks3D::new(layer_count);
+            let layer_options = (1 << layer_count) - 1;
+            assert_eq!(
+                p.legal_twists().count(),
+                p.twist_axes().len() * p.twist_directions().len() * layer_options,
+            );
+        }
+
+        // Twisting with an invalid axis is an error rather than a panic.
+        let mut p = Rubiks3D::new(3);
+        let twist = Twist {
+            axis: TwistAxis(p.twist_axes().len() as _),
+            direction: TwistDirection(0),
+            layers: LayerMask(1),
+        };
+        assert_eq!(p.twist(twist), Err("invalid twist axis"));
+    }
+
+    #[test]
+    fn test_rubiks_3d_face_adjacency() {
+        let p = Rubiks3D::new(3);
+        let adjacency = p.face_adjacency();
+        assert_eq!(adjacency.len(), 6);
+        for (i, adjacent_faces) in adjacency.iter().enumerate() {
+            let face = FaceEnum::from(Face(i as _));
+            assert_eq!(adjacent_faces.len(), 4);
+            assert!(!adjacent_faces.contains(&Face::from(face)));
+            assert!(!adjacent_faces.contains(&Face::from(face.opposite())));
+        }
+    }
+
+    #[test]
+    fn test_rubiks_3d_sticker_pieces() {
+        for layer_count in 1..=6 {
+            let p = Rubiks3D::new(layer_count);
+            assert_eq!(p.check_sticker_pieces(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_rubiks_3d_solve_hint() {
+        let solved = Rubiks3D::new(3);
+        assert_eq!(solved.solve_hint(), None);
+        assert_eq!(Rubiks3D::new(4).solve_hint(), None);
+
+        for twist in solved.legal_twists() {
+            if twist.layers != LayerMask(1) {
+                continue;
+            }
+            let mut p = solved.clone();
+            p.twist(twist).unwrap();
+            let hint = p.solve_hint().unwrap();
+            assert_eq!(hint.len(), 1, "hint for {twist:?} is {hint:?}");
+            p.twist(hint[0]).unwrap();
+            assert!(p.is_solved(), "hint for {twist:?} is {hint:?}");
+        }
+    }
+
+    #[test]
+    fn test_rubiks_3d_hint_search_steps() {
+        let mut p = Rubiks3D::new(3);
+        p.scramble(1);
+        let expected = p.solve_hint();
+        assert!(expected.is_some());
+
+        // The search is split into many small steps.
+        let mut search = p.hint_search().unwrap();
+        let mut steps = 1;
+        let hint = loop {
+            match search.step() {
+                Poll::Ready(hint) => break hint,
+                Poll::Pending => steps += 1,
+            }
+        };
+        assert!(steps > 1);
+        assert_eq!(hint, expected);
+    }
+
+    #[test]
+    fn test_rubiks_3d_solve_hint_mid_solve() {
+        let mut p = Rubiks3D::new(3);
+        p.scramble(1);
+        let stages = p.hint_stages();
+        let center_faces = p.hint_center_faces();
+
+        // Each hint makes progress from a fully scrambled state, not only from
+        // states close to solved.
+        for _ in 0..2 {
+            let old_score = p.hint_score(&stages, &center_faces);
+            let hint = p.solve_hint().unwrap();
+            assert!(hint.len() <= HINT_MAX_DEPTH);
+            for twist in hint {
+                p.twist(twist).unwrap();
+            }
+            assert!(p.hint_score(&stages, &center_faces) > old_score);
+        }
+    }
+
+    #[test]
+    fn test_rubiks_3d_twist_order() {
+        let p = Rubiks3D::new(3);
+        for axis in (0..p.twist_axes().len() as _).map(TwistAxis) {
+            assert_eq!(p.twist_axis_order(axis), 4);
+        }
+        let order_of = |dir: TwistDirectionEnum| p.twist_direction_order(dir.into());
+        assert_eq!(order_of(TwistDirectionEnum::CW90), 4);
+        assert_eq!(order_of(TwistDirectionEnum::CCW90), 4);
+        assert_eq!(order_of(TwistDirectionEnum::CW180), 2);
+    }
+
+    #[test]
+    fn test_rubiks_3d_sticker_normals_point_outward() {
+        let p = Rubiks3D::new(3);
+        let mut params = StickerGeometryParams::new(
+            &crate::preferences::ViewPreferences::default(),
+            p.ty(),
+            None,
+            Quaternion::one(),
+        );
+        params.view_transform = Matrix3::identity();
+        params.show_frontfaces = true;
+        params.show_backfaces = true;
+
+        for sticker in (0..p.stickers().len() as _).map(Sticker) {
+            let geom = p.sticker_geometry(sticker, params).unwrap();
+            let outward = p.sticker_face(sticker).vector();
+            let [front, back] = [0, 1].map(|i| {
+                geometry::polygon_normal_from_indices(&geom.verts, &geom.polygon_indices[i])
+            });
+            assert!(
+                front.dot(outward) > 0.0,
+                "front face of {sticker:?} points inward",
+            );
+            assert!(
+                back.dot(outward) < 0.0,
+                "back face of {sticker:?} points outward",
+            );
+        }
+    }
+
+    #[test]
+    fn test_rubiks_3d_pieces_are_distinct() {
+        for layer_count in 1..=6 {
+            let p = Rubiks3D::new(layer_count);
+            crate::puzzle::tests::test_pieces_are_distinct(&p, |piece| p.piece_location(piece));
+        }
+    }
+
     fn twist_comparison_key(p: &Rubiks3D, twist: Twist) -> impl PartialEq {
         const SOME_PROGRESS: f32 = 0.1;
 
@@ -177,9 +177,7 @@ fn puzzle_description(layer_count: u8) -> &'static Rubiks4DDescription {
             aliases,
         };
 
-        // It's not like we'll ever clear the cache anyway, so just leak it
-        // and let us have the 'static lifetimes.
-        Box::leak(Box::new(Rubiks4DDescription {
+        let desc = Rubiks4DDescription {
             name: format!("{0}x{0}x{0}x{0}", layer_count),
 
             layer_count,
@@ -196,7 +194,12 @@ fn puzzle_description(layer_count: u8) -> &'static Rubiks4DDescription {
             notation,
 
             piece_locations,
-        }))
+        };
+        debug_assert_eq!(desc.check_sticker_pieces(), Ok(()));
+
+        // It's not like we'll ever clear the cache anyway, so just leak it
+        // and let us have the 'static lifetimes.
+        Box::leak(Box::new(desc))
     })
 }
 
@@ -288,6 +291,15 @@ impl PuzzleType for Rubiks4DDescription {
             UFR | DBL | UFL | DBR | DFR | UBL | UBR | DFL => 2,
         }
     }
+    fn twist_axis_normal(&self, twist_axis: TwistAxis) -> Vec<f32> {
+        <[f32; 4]>::from(FaceEnum::from(twist_axis).vector()).to_vec()
+    }
+    fn twist_transform(&self, twist_axis: TwistAxis, direction: TwistDirection) -> Vec<Vec<f32>> {
+        let matrix = FaceEnum::from(twist_axis).twist_matrix(direction.into(), 1.0);
+        (0..4)
+            .map(|i| <[f32; 4]>::from(matrix.row(i)).to_vec())
+            .collect()
+    }
 
     fn make_recenter_twist(&self, axis: TwistAxis) -> Result<Twist, String> {
         use FaceEnum::*;
@@ -413,11 +425,15 @@ impl IndexMut<Piece> for Rubiks4D {
 }
 impl PuzzleState for Rubiks4D {
     fn twist(&mut self, twist: Twist) -> Result<(), &'static str> {
+        self.can_twist(twist)?;
         for piece in self.pieces_affected_by_twist(twist) {
             self[piece] = self[piece].twist(twist.axis.into(), twist.direction.into());
         }
         Ok(())
     }
+    fn reset(&mut self) {
+        self.piece_states.fill(PieceState::default());
+    }
     fn layer_from_twist_axis(&self, twist_axis: TwistAxis, piece: Piece) -> u8 {
         let face: FaceEnum = twist_axis.into();
         let face_coord = match face.sign() {
@@ -541,6 +557,42 @@ impl PuzzleState for Rubiks4D {
         }
         true
     }
+
+    fn snapshot(&self) -> PuzzleSnapshot {
+        PuzzleSnapshot {
+            ty: self.ty(),
+            piece_orientations: self
+                .piece_states
+                .iter()
+                .map(|state| state.0.iter().map(|&face| face as u8).collect())
+                .collect(),
+        }
+    }
+    fn load_snapshot(&mut self, snapshot: &PuzzleSnapshot) -> Result<(), &'static str> {
+        if snapshot.ty != self.ty() {
+            return Err("snapshot is for a different puzzle type");
+        }
+        if snapshot.piece_orientations.len() != self.piece_states.len() {
+            return Err("snapshot has wrong number of pieces");
+        }
+        let new_state = Self {
+            desc: self.desc,
+            piece_states: snapshot
+                .piece_orientations
+                .iter()
+                .map(|faces| PieceState::from_face_ids(faces))
+                .collect::<Option<_>>()
+                .ok_or("snapshot has invalid piece orientation")?,
+        };
+        let locations_are_unique = (0..new_state.piece_states.len())
+            .map(|i| new_state.piece_location(Piece(i as _)))
+            .all_unique();
+        if !locations_are_unique {
+            return Err("snapshot has multiple pieces in the same location");
+        }
+        *self = new_state;
+        Ok(())
+    }
 }
 #[delegate_to_methods]
 #[delegate(PuzzleType, target_ref = "desc")]
@@ -749,6 +801,19 @@ impl IndexMut<Axis> for PieceState {
     }
 }
 impl PieceState {
+    /// Constructs a piece state from face IDs, returning `None` if they do not
+    /// form a valid (non-mirrored) orientation.
+    fn from_face_ids(face_ids: &[u8]) -> Option<Self> {
+        let faces: [FaceEnum; 4] = face_ids
+            .iter()
+            .map(|&i| FaceEnum::iter().nth(i as usize))
+            .collect::<Option<Vec<_>>>()?
+            .try_into()
+            .ok()?;
+        is_proper_orientation(&faces.map(|face| (face.axis() as u8, face.sign())))
+            .then_some(Self(faces))
+    }
+
     #[must_use]
     fn rotate(mut self, from: Axis, to: Axis) -> Self {
         let diff = (from as u8 ^ to as u8) << 1;
@@ -1484,6 +1549,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rubiks_4d_pieces_are_distinct() {
+        for layer_count in 1..=5 {
+            let p = Rubiks4D::new(layer_count);
+            crate::puzzle::tests::test_pieces_are_distinct(&p, |piece| p.piece_location(piece));
+        }
+    }
+
+    #[test]
+    fn test_rubiks_4d_sticker_pieces() {
+        for layer_count in 1..=5 {
+            let p = Rubiks4D::new(layer_count);
+            assert_eq!(p.check_sticker_pieces(), Ok(()));
+        }
+    }
+
     fn twist_comparison_key(p: &Rubiks4D, twist: Twist) -> impl PartialEq {
         const SOME_PROGRESS: f32 = 0.1;
 
@@ -0,0 +1,119 @@
+//! Summary statistics about a puzzle type.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use itertools::Itertools;
+
+use super::*;
+
+/// Summary statistics about a puzzle type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleStats {
+    pub piece_count: usize,
+    pub sticker_count: usize,
+    pub face_count: usize,
+    pub piece_type_count: usize,
+    /// Number of distinct orientations of the whole puzzle.
+    pub symmetry_order: usize,
+    /// Name and order of each twist axis.
+    pub axis_orders: Vec<(&'static str, usize)>,
+}
+impl PuzzleStats {
+    /// Returns statistics for a puzzle type, computing them the first time
+    /// they are requested.
+    pub fn get(ty: PuzzleTypeEnum) -> Self {
+        lazy_static! {
+            static ref CACHE: Mutex<HashMap<PuzzleTypeEnum, PuzzleStats>> =
+                Mutex::new(HashMap::new());
+        }
+
+        CACHE
+            .lock()
+            .unwrap()
+            .entry(ty)
+            .or_insert_with(|| Self::compute(ty))
+            .clone()
+    }
+
+    fn compute(ty: PuzzleTypeEnum) -> Self {
+        Self {
+            piece_count: ty.pieces().len(),
+            sticker_count: ty.stickers().len(),
+            face_count: ty.faces().len(),
+            piece_type_count: ty.piece_types().len(),
+            symmetry_order: symmetry_order(ty),
+            axis_orders: (0..ty.twist_axes().len() as u8)
+                .map(TwistAxis)
+                .map(|axis| (ty.info(axis).name, ty.twist_axis_order(axis)))
+                .collect(),
+        }
+    }
+}
+
+/// Counts the orientations of the whole puzzle that are reachable by rotating
+/// it using twists of all layers.
+fn symmetry_order(ty: PuzzleTypeEnum) -> usize {
+    // The rotations of the whole puzzle do not depend on the layer count, so
+    // search them using the smallest puzzle of the same family.
+    let ty = match ty {
+        PuzzleTypeEnum::Rubiks3D { .. } => PuzzleTypeEnum::Rubiks3D { layer_count: 1 },
+        PuzzleTypeEnum::Rubiks4D { .. } => PuzzleTypeEnum::Rubiks4D { layer_count: 1 },
+    };
+
+    let rotations = (0..ty.twist_axes().len() as u8)
+        .flat_map(|axis| {
+            (0..ty.twist_directions().len() as u8).map(move |direction| Twist {
+                axis: TwistAxis(axis),
+                direction: TwistDirection(direction),
+                layers: ty.all_layers(),
+            })
+        })
+        .collect_vec();
+
+    let initial = Puzzle::new(ty);
+    let mut seen = HashSet::from([initial.snapshot()]);
+    let mut queue = vec![initial];
+    while let Some(puzzle) = queue.pop() {
+        for &twist in &rotations {
+            let mut next = puzzle.clone();
+            if next.twist(twist).is_ok() && seen.insert(next.snapshot()) {
+                queue.push(next);
+            }
+        }
+    }
+    seen.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rubiks_3d_stats() {
+        let stats = PuzzleStats::get(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        assert_eq!(stats.piece_count, 26);
+        assert_eq!(stats.sticker_count, 54);
+        assert_eq!(stats.face_count, 6);
+        assert_eq!(stats.piece_type_count, 3);
+        assert_eq!(stats.symmetry_order, 24);
+        assert_eq!(stats.axis_orders.len(), 6);
+        assert!(stats.axis_orders.iter().all(|&(_, order)| order == 4));
+    }
+
+    #[test]
+    fn test_rubiks_4d_symmetry_order() {
+        let stats = PuzzleStats::get(PuzzleTypeEnum::Rubiks4D { layer_count: 1 });
+        assert_eq!(stats.symmetry_order, 192);
+    }
+
+    #[test]
+    fn test_symmetry_order_ignores_layer_count() {
+        for layer_count in [2, 9] {
+            let ty = PuzzleTypeEnum::Rubiks3D { layer_count };
+            assert_eq!(symmetry_order(ty), 24);
+        }
+        let ty = PuzzleTypeEnum::Rubiks4D { layer_count: 4 };
+        assert_eq!(symmetry_order(ty), 192);
+    }
+}
@@ -0,0 +1,228 @@
+//! Description of a puzzle's move structure, for use by external tools.
+
+use serde::{Deserialize, Serialize};
+
+use super::notation::{Alias, TwistDirectionName};
+use super::*;
+
+/// Description of the twist axes, twist directions, and notation of a puzzle
+/// type.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TwistTable {
+    pub puzzle_type: PuzzleTypeEnum,
+    pub name: String,
+    pub layer_count: u8,
+    pub axes: Vec<TwistAxisEntry>,
+    pub directions: Vec<TwistDirectionEntry>,
+    pub notation: NotationEntry,
+}
+
+/// Description of a twist axis.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TwistAxisEntry {
+    pub name: String,
+    /// Name of the opposite twist axis, if there is one.
+    pub opposite: Option<String>,
+    /// Outward unit vector perpendicular to the layers of the twist axis.
+    pub normal: Vec<f32>,
+    /// Distance of each cut from the center of the puzzle, from outermost to
+    /// innermost, where the outermost face is at distance 1.
+    pub cuts: Vec<f32>,
+}
+
+/// Description of a twist direction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TwistDirectionEntry {
+    pub name: String,
+    pub symbol: String,
+    /// Name of the reverse twist direction.
+    pub reverse: String,
+    /// Number of quarter turns in a single-layer twist in this direction.
+    pub quarter_turns: usize,
+    /// Matrix, as a list of rows, that transforms the pieces affected by a
+    /// twist in this direction, indexed by twist axis.
+    pub transforms: Vec<Vec<Vec<f32>>>,
+}
+
+/// Description of a notation scheme.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NotationEntry {
+    /// Symbol for each twist axis.
+    pub axis_symbols: Vec<String>,
+    /// Symbol for each twist direction on each twist axis, indexed first by
+    /// twist axis and then by twist direction.
+    pub direction_symbols: Vec<Vec<String>>,
+    /// Suffix for a block of layers from the outermost layer, such as `w`.
+    pub block_suffix: Option<String>,
+    /// Aliases for combinations of twist axis, layers, and (optionally)
+    /// direction.
+    pub aliases: Vec<NotationAliasEntry>,
+}
+
+/// Description of a notation alias, such as `x` or `M`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NotationAliasEntry {
+    pub symbol: String,
+    pub axis: String,
+    pub layers: u32,
+    /// Twist direction, if the alias includes one.
+    pub direction: Option<String>,
+}
+
+impl TwistTable {
+    /// Constructs the twist table for a puzzle type.
+    pub fn new(ty: PuzzleTypeEnum) -> Self {
+        let axes = (0..ty.twist_axes().len() as u8).map(TwistAxis);
+        let directions = (0..ty.twist_directions().len() as u8).map(TwistDirection);
+        let axis_name = |axis: TwistAxis| ty.info(axis).name.to_string();
+        let direction_name = |direction: TwistDirection| ty.info(direction).name.to_string();
+
+        let notation = ty.notation_scheme();
+
+        Self {
+            puzzle_type: ty,
+            name: ty.name().to_string(),
+            layer_count: ty.layer_count(),
+            axes: axes
+                .clone()
+                .map(|axis| TwistAxisEntry {
+                    name: axis_name(axis),
+                    opposite: ty.opposite_twist_axis(axis).map(axis_name),
+                    normal: ty.twist_axis_normal(axis),
+                    cuts: ty.twist_axis_cuts(axis),
+                })
+                .collect(),
+            directions: directions
+                .map(|direction| TwistDirectionEntry {
+                    name: direction_name(direction),
+                    symbol: ty.info(direction).symbol.to_string(),
+                    reverse: direction_name(ty.reverse_twist_direction(direction)),
+                    quarter_turns: ty.count_quarter_turns(Twist {
+                        axis: TwistAxis(0),
+                        direction,
+                        layers: LayerMask(1),
+                    }),
+                    transforms: axes
+                        .clone()
+                        .map(|axis| ty.twist_transform(axis, direction))
+                        .collect(),
+                })
+                .collect(),
+            notation: NotationEntry {
+                axis_symbols: notation.axis_names.clone(),
+                direction_symbols: axes
+                    .map(|axis| {
+                        notation
+                            .direction_names
+                            .iter()
+                            .map(|name| match name {
+                                TwistDirectionName::Same(s) => s.clone(),
+                                TwistDirectionName::PerAxis(v) => v[axis.0 as usize].clone(),
+                            })
+                            .collect()
+                    })
+                    .collect(),
+                block_suffix: notation.block_suffix.clone(),
+                aliases: notation
+                    .aliases
+                    .iter()
+                    .map(|(symbol, alias)| {
+                        let (axis, layers, direction) = match *alias {
+                            Alias::AxisLayers(axis, layers) => (axis, layers, None),
+                            Alias::EntireTwist(twist) => {
+                                (twist.axis, twist.layers, Some(twist.direction))
+                            }
+                        };
+                        NotationAliasEntry {
+                            symbol: symbol.clone(),
+                            axis: axis_name(axis),
+                            layers: layers.0,
+                            direction: direction.map(direction_name),
+                        }
+                    })
+                    .collect(),
+            },
+        }
+    }
+
+    /// Serializes the twist table to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("error serializing twist table")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rubiks_3d_twist_table_json() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let json = TwistTable::new(ty).to_json();
+        let table: TwistTable = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(table.puzzle_type, ty);
+        assert_eq!(table.axes.len(), 6);
+        assert_eq!(table.notation.axis_symbols.len(), 6);
+        for axis in &table.axes {
+            let opposite = axis.opposite.as_ref().unwrap();
+            let opposite_entry = table.axes.iter().find(|a| &a.name == opposite).unwrap();
+            assert_eq!(opposite_entry.opposite.as_ref(), Some(&axis.name));
+
+            let negated_normal = axis.normal.iter().map(|x| -x).collect::<Vec<_>>();
+            assert_eq!(opposite_entry.normal, negated_normal);
+            assert_eq!(axis.normal.iter().map(|x| x * x).sum::<f32>(), 1.0);
+            assert_eq!(axis.cuts.len(), 2);
+            assert!((axis.cuts[0] - 1.0 / 3.0).abs() < 1e-5);
+            assert!((axis.cuts[1] + 1.0 / 3.0).abs() < 1e-5);
+        }
+
+        let direction_names = table.directions.iter().map(|d| d.name.as_str());
+        assert!(direction_names.eq(ty.twist_directions().iter().map(|d| d.name)));
+        for direction in &table.directions {
+            let reverse = table
+                .directions
+                .iter()
+                .find(|d| d.name == direction.reverse)
+                .unwrap();
+            assert_eq!(reverse.reverse, direction.name);
+            assert_eq!(reverse.quarter_turns, direction.quarter_turns);
+
+            // Each transform fixes the normal of its twist axis.
+            assert_eq!(direction.transforms.len(), 6);
+            for (axis, transform) in table.axes.iter().zip(&direction.transforms) {
+                assert_transform_fixes(transform, &axis.normal);
+            }
+        }
+
+        assert!(table.notation.aliases.iter().any(|a| a.symbol == "x"));
+    }
+
+    #[test]
+    fn test_rubiks_4d_twist_table_geometry() {
+        let ty = PuzzleTypeEnum::Rubiks4D { layer_count: 2 };
+        let table = TwistTable::new(ty);
+
+        assert_eq!(table.axes.len(), 8);
+        for axis in &table.axes {
+            assert_eq!(axis.normal.len(), 4);
+            assert_eq!(axis.cuts, [0.0]);
+        }
+        for direction in &table.directions {
+            for (axis, transform) in table.axes.iter().zip(&direction.transforms) {
+                assert_eq!(transform.len(), 4);
+                assert_transform_fixes(transform, &axis.normal);
+            }
+        }
+    }
+
+    fn assert_transform_fixes(transform: &[Vec<f32>], v: &[f32]) {
+        for (row, &expected) in transform.iter().zip(v) {
+            let actual: f32 = row.iter().zip(v).map(|(a, b)| a * b).sum();
+            assert!(
+                (actual - expected).abs() < 1e-5,
+                "{transform:?} moves {v:?}"
+            );
+        }
+    }
+}
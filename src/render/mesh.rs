@@ -9,6 +9,12 @@ use crate::puzzle::*;
 use crate::util::IterCyclicPairsExt;
 
 const OUTLINE_SCALE: f32 = 1.0 / 512.0;
+/// Maximum outline size, matching the range allowed in the preferences UI.
+const MAX_OUTLINE_SIZE: f32 = 5.0;
+/// Maximum outline radius, as a fraction of the size of the whole puzzle on
+/// screen.
+const MAX_OUTLINE_RADIUS_PER_PUZZLE_SIZE: f32 = 0.02;
+const TWIST_PREVIEW_OPACITY: f32 = 0.3;
 const OUTLINE_WEDGE_VERTS_PER_RADIAN: f32 = 3.0;
 
 pub(super) fn make_puzzle_mesh(
@@ -26,7 +32,10 @@ pub(super) fn make_puzzle_mesh(
     // incrementation for each sticker to get the next-largest `f32` value.
     let mut z = 0.5_f32;
 
-    let face_colors = &prefs.colors.face_colors_list(puzzle.ty());
+    let sticker_colors = puzzle.sticker_colors(prefs);
+    let changed_pieces = puzzle.changed_pieces();
+
+    let max_outline_radius = puzzle_size(sticker_geometries) * MAX_OUTLINE_RADIUS_PER_PUZZLE_SIZE;
 
     for geom in sticker_geometries {
         let sticker_info = puzzle.info(geom.sticker);
@@ -34,21 +43,39 @@ pub(super) fn make_puzzle_mesh(
         let visual_state = puzzle.visual_piece_state(sticker_info.piece);
 
         // Determine sticker alpha.
-        let alpha = visual_state.opacity(prefs);
+        let mut alpha = visual_state.opacity(prefs);
+        if geom.is_preview {
+            alpha *= TWIST_PREVIEW_OPACITY;
+        }
+        if let Some(changed_pieces) = &changed_pieces {
+            if !changed_pieces[sticker_info.piece.0 as usize] {
+                alpha *= prefs.opacity.hidden;
+            }
+        }
+        if puzzle.is_hidden_by_layer_scrub(sticker_info.piece) {
+            alpha *= prefs.opacity.hidden;
+        }
 
         // Determine sticker fill color.
-        let sticker_color = egui::Rgba::from(if prefs.colors.blindfold {
+        let face_color = if prefs.colors.blindfold {
             prefs.colors.blind_face
         } else {
-            face_colors[puzzle.info(geom.sticker).color.0 as usize]
-        })
-        .multiply(alpha);
+            sticker_colors[geom.sticker.0 as usize]
+        };
+        let sticker_color = egui::Rgba::from(face_color).multiply(alpha);
 
         // Determine outline appearance.
         let outline_color = visual_state
-            .outline_color(prefs, puzzle.selection().contains(&geom.sticker))
+            .outline_color(
+                prefs,
+                face_color,
+                puzzle.selection().contains(&geom.sticker),
+            )
             .multiply(alpha);
-        let outline_size = visual_state.outline_size(prefs);
+        let outline_size = match geom.is_preview {
+            true => 0.0,
+            false => visual_state.outline_size(prefs),
+        };
 
         // Generate outline vertices.
         if outline_size > 0.0 {
@@ -71,7 +98,7 @@ pub(super) fn make_puzzle_mesh(
                 &mut verts,
                 &mut indices,
                 &outlines,
-                outline_size,
+                outline_radius(outline_size, max_outline_radius),
                 |Point2 { x, y }| RgbaVertex {
                     pos: [x, y, z],
                     color: outline_color.to_array(),
@@ -107,11 +134,9 @@ fn generate_outline_geometry(
     verts_out: &mut Vec<RgbaVertex>,
     indices_out: &mut Vec<u32>,
     lines: &[[Point2<f32>; 2]],
-    outline_size: f32,
+    outline_radius: f32,
     make_vert: impl Copy + Fn(Point2<f32>) -> RgbaVertex,
 ) {
-    let outline_radius = outline_size * OUTLINE_SCALE;
-
     let mut unique_line_ends: Vec<Point2<f32>> = vec![];
 
     // Generate simple lines.
@@ -188,3 +213,135 @@ fn generate_outline_geometry(
         }
     }
 }
+
+/// Returns the size of the whole puzzle on screen, which is the larger of the
+/// width and height of its bounding box.
+fn puzzle_size(sticker_geometries: &[ProjectedStickerGeometry]) -> f32 {
+    let Some(first) = sticker_geometries.first() else {
+        return 0.0;
+    };
+    let (min, max) =
+        sticker_geometries
+            .iter()
+            .fold((first.min_bound, first.max_bound), |(min, max), geom| {
+                (
+                    point3(
+                        min.x.min(geom.min_bound.x),
+                        min.y.min(geom.min_bound.y),
+                        0.0,
+                    ),
+                    point3(
+                        max.x.max(geom.max_bound.x),
+                        max.y.max(geom.max_bound.y),
+                        0.0,
+                    ),
+                )
+            });
+    f32::max(max.x - min.x, max.y - min.y)
+}
+
+/// Returns the radius of the outline around a sticker, clamped to the
+/// maximum outline size and to `max_radius`, which depends on the size of the
+/// whole puzzle so that outlines cannot cover it.
+fn outline_radius(outline_size: f32, max_radius: f32) -> f32 {
+    f32::min(
+        outline_size.min(MAX_OUTLINE_SIZE) * OUTLINE_SCALE,
+        max_radius,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outline_radius_clamping() {
+        // A puzzle that fills a typical view.
+        let max_radius = 1.5 * MAX_OUTLINE_RADIUS_PER_PUZZLE_SIZE;
+
+        // Normal sizes are unchanged.
+        assert_eq!(outline_radius(1.0, max_radius), OUTLINE_SCALE);
+        assert_eq!(outline_radius(3.0, max_radius), 3.0 * OUTLINE_SCALE);
+        assert_eq!(
+            outline_radius(MAX_OUTLINE_SIZE, max_radius),
+            MAX_OUTLINE_SIZE * OUTLINE_SCALE,
+        );
+
+        // Extreme sizes are clamped.
+        assert_eq!(
+            outline_radius(1000.0, max_radius),
+            MAX_OUTLINE_SIZE * OUTLINE_SCALE,
+        );
+
+        // Tiny puzzles get tiny outlines.
+        let tiny_max_radius = 0.01 * MAX_OUTLINE_RADIUS_PER_PUZZLE_SIZE;
+        let r = outline_radius(1000.0, tiny_max_radius);
+        assert_eq!(r, tiny_max_radius);
+
+        // The uploaded vertices stay near the sticker.
+        let square = [
+            point2(0.0, 0.0),
+            point2(0.001, 0.0),
+            point2(0.001, 0.001),
+            point2(0.0, 0.001),
+        ];
+        let lines = square
+            .into_iter()
+            .cyclic_pairs()
+            .map(|(a, b)| [a, b])
+            .collect_vec();
+        let mut verts = vec![];
+        let mut indices = vec![];
+        generate_outline_geometry(&mut verts, &mut indices, &lines, r, |p| RgbaVertex {
+            pos: [p.x, p.y, 0.5],
+            color: [1.0; 4],
+        });
+        for v in verts {
+            assert!(v.pos[0].abs() <= 0.001 + r * 1.01);
+            assert!(v.pos[1].abs() <= 0.001 + r * 1.01);
+        }
+    }
+
+    #[test]
+    fn test_puzzle_size() {
+        let mut puzzle = PuzzleController::default();
+        let geometry = puzzle.geometry(&crate::preferences::DEFAULT_PREFS);
+        let size = puzzle_size(&geometry);
+        assert!(size > 0.5 && size < 4.0, "{size}");
+        assert_eq!(puzzle_size(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_sticker_color_override_mesh() {
+        let color = egui::Color32::from_rgb(255, 0, 255);
+        let is_override_color = |v: &RgbaVertex| {
+            let [r, g, b, _] = v.color;
+            r > 0.0 && g == 0.0 && r == b
+        };
+
+        let mut prefs = crate::preferences::DEFAULT_PREFS.clone();
+        let mut puzzle = PuzzleController::default();
+        let geometry = puzzle.geometry(&prefs);
+        let (verts, _) = make_puzzle_mesh(&mut puzzle, &prefs, &geometry);
+        assert!(!verts.iter().any(is_override_color));
+
+        // Override the color of every visible sticker.
+        for geom in &*geometry {
+            prefs
+                .colors
+                .set_sticker_color(puzzle.ty(), geom.sticker, Some(color));
+        }
+        let (verts, _) = make_puzzle_mesh(&mut puzzle, &prefs, &geometry);
+        assert!(verts.iter().any(is_override_color));
+
+        // The lookup is only rebuilt when the preferences change.
+        let colors = puzzle.sticker_colors(&prefs);
+        assert!(std::sync::Arc::ptr_eq(
+            &colors,
+            &puzzle.sticker_colors(&prefs)
+        ));
+        assert!(colors.contains(&color));
+        prefs.colors.sticker_overrides[puzzle.ty()].clear();
+        assert!(!puzzle.sticker_colors(&prefs).contains(&color));
+    }
+}
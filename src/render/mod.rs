@@ -1,6 +1,7 @@
 //! Rendering logic.
 
 use instant::Instant;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 mod cache;
@@ -10,9 +11,10 @@ mod state;
 mod structs;
 
 use crate::app::App;
-use crate::puzzle::ProjectedStickerGeometry;
+use crate::preferences::{Preferences, StereoMode, ViewPreferences};
+use crate::puzzle::{ProjectedStickerGeometry, PuzzleController};
 use cache::{CachedDynamicBuffer, CachedUniformBuffer};
-pub(crate) use state::GraphicsState;
+pub use state::GraphicsState;
 use structs::*;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -24,6 +26,9 @@ struct PuzzleRenderParams {
     scale: f32,
     align_h: f32,
     align_v: f32,
+
+    stereo_mode: StereoMode,
+    stereo_eye_separation: f32,
 }
 
 pub(crate) struct PuzzleRenderCache {
@@ -39,7 +44,9 @@ pub(crate) struct PuzzleRenderCache {
     out_texture: Option<(wgpu::Texture, wgpu::TextureView)>,
     depth_texture: Option<(wgpu::Texture, wgpu::TextureView)>,
 
-    basic_pipeline: Option<wgpu::RenderPipeline>,
+    /// Pipelines for drawing stickers, keyed by the color channels they
+    /// write to.
+    basic_pipelines: HashMap<wgpu::ColorWrites, wgpu::RenderPipeline>,
 }
 impl Default for PuzzleRenderCache {
     fn default() -> Self {
@@ -62,7 +69,7 @@ impl Default for PuzzleRenderCache {
             out_texture: None,
             depth_texture: None,
 
-            basic_pipeline: None,
+            basic_pipelines: HashMap::new(),
         }
     }
 }
@@ -88,7 +95,7 @@ impl PuzzleRenderCache {
             self.multisample_texture = None;
             self.depth_texture = None;
 
-            self.basic_pipeline = None;
+            self.basic_pipelines.clear();
         }
 
         self.last_params = Some(new);
@@ -102,14 +109,19 @@ pub(crate) fn draw_puzzle(
     gfx: &mut GraphicsState,
     mut force_redraw: bool,
 ) -> Option<wgpu::TextureView> {
-    let (width, height) = app.puzzle_texture_size;
-    let size = cgmath::vec2(width as f32, height as f32);
-
     // Avoid divide-by-zero errors.
-    if width == 0 || height == 0 {
+    if app.puzzle_texture_size.0 == 0 || app.puzzle_texture_size.1 == 0 {
         return None;
     }
 
+    // Render at a different resolution than the target if requested. The
+    // texture is resampled when it is drawn.
+    let max_texture_dimension = gfx.device.limits().max_texture_dimension_2d;
+    let (width, height) = app
+        .prefs
+        .gfx
+        .scaled_texture_size(app.puzzle_texture_size, max_texture_dimension);
+
     // Disable MSAA on web.
     #[cfg(target_arch = "wasm32")]
     {
@@ -137,14 +149,10 @@ pub(crate) fn draw_puzzle(
         scale: view_prefs.scale,
         align_h: view_prefs.align_h,
         align_v: view_prefs.align_v,
-    });
 
-    // Calculate scale.
-    let scale = {
-        let min_dimen = f32::min(size.x, size.y);
-        let pixel_scale = min_dimen * view_prefs.scale;
-        cgmath::vec2(pixel_scale / size.x, pixel_scale / size.y)
-    };
+        stereo_mode: prefs.gfx.stereo_mode,
+        stereo_eye_separation: prefs.gfx.stereo_eye_separation,
+    });
 
     // If the puzzle geometry has changed, force a redraw.
     let puzzle_geometry = puzzle.geometry(prefs);
@@ -158,15 +166,25 @@ pub(crate) fn draw_puzzle(
     cache.last_puzzle_geometry = Some(Arc::clone(&puzzle_geometry));
 
     // Determine which sticker(s) are at the mouse cursor, in order from front
-    // to back.
-    if let Some(cursor_pos) = app.cursor_pos {
-        let transformed_cursor_pos = cgmath::point2(
-            (cursor_pos.x - view_prefs.align_h) / scale.x,
-            (cursor_pos.y - view_prefs.align_v) / scale.y,
-        );
-        let hovered_stickers = puzzle_geometry.iter().rev().filter_map(move |geom| {
-            Some((geom.sticker, geom.twists_for_point(transformed_cursor_pos)?))
-        });
+    // to back, as seen by the eye whose viewport is under the cursor. In
+    // anaglyph mode, both eyes cover the whole viewport, so the left eye is
+    // used.
+    let eyes = eye_views(puzzle, prefs, width);
+    let hovered_eye = app.cursor_pos.and_then(|cursor_pos| {
+        eyes.iter().find_map(|eye| {
+            let pos = eye.cursor_to_puzzle_coords(cursor_pos, (width, height), &view_prefs)?;
+            Some((eye, pos))
+        })
+    });
+    if let Some((eye, transformed_cursor_pos)) = hovered_eye {
+        let hovered_stickers = eye
+            .geometry
+            .iter()
+            .rev()
+            .filter(|geom| !geom.is_preview)
+            .filter_map(move |geom| {
+                Some((geom.sticker, geom.twists_for_point(transformed_cursor_pos)?))
+            });
         puzzle.update_hovered_sticker(hovered_stickers);
     } else {
         puzzle.update_hovered_sticker([]);
@@ -181,8 +199,140 @@ pub(crate) fn draw_puzzle(
         return None; // No repaint needed.
     }
 
-    // Generate the mesh.
-    let (mut verts, mut indices) = mesh::make_puzzle_mesh(puzzle, prefs, &puzzle_geometry);
+    let background = background_color(prefs, false);
+    let out_texture = render_puzzle(
+        cache,
+        gfx,
+        puzzle,
+        prefs,
+        (width, height),
+        &eyes,
+        background,
+    );
+
+    Some(out_texture.create_view(&wgpu::TextureViewDescriptor::default()))
+}
+
+/// Renders the puzzle offscreen at the given resolution and returns the image
+/// encoded as a PNG.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn capture_puzzle_image(
+    gfx: &GraphicsState,
+    puzzle: &mut PuzzleController,
+    prefs: &Preferences,
+    (width, height): (u32, u32),
+    transparent_background: bool,
+) -> Result<Vec<u8>, String> {
+    let swap_red_blue = match gfx.config.format {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        other => return Err(format!("unsupported texture format {other:?}")),
+    };
+
+    let max_texture_dimension = gfx.device.limits().max_texture_dimension_2d;
+    let width = width.clamp(1, max_texture_dimension);
+    let height = height.clamp(1, max_texture_dimension);
+
+    // Render using a separate cache so that the on-screen puzzle texture is
+    // unaffected.
+    let mut cache = PuzzleRenderCache::default();
+    let eyes = eye_views(puzzle, prefs, width);
+    let background = background_color(prefs, transparent_background);
+    let out_texture = render_puzzle(
+        &mut cache,
+        gfx,
+        puzzle,
+        prefs,
+        (width, height),
+        &eyes,
+        background,
+    );
+
+    // Copy the texture into a buffer. Each row must be padded to a multiple
+    // of `COPY_BYTES_PER_ROW_ALIGNMENT`.
+    let unpadded_bytes_per_row = width * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = (unpadded_bytes_per_row + align - 1) / align * align;
+    let buffer = gfx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("puzzle_screenshot_buffer"),
+        size: padded_bytes_per_row as u64 * height as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = gfx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("puzzle_screenshot_command_encoder"),
+        });
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture: out_texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(padded_bytes_per_row),
+                rows_per_image: None,
+            },
+        },
+        extent3d(width, height),
+    );
+    gfx.queue.submit(std::iter::once(encoder.finish()));
+
+    // Read the buffer back from the GPU.
+    let buffer_slice = buffer.slice(..);
+    let (tx, rx) = std::sync::mpsc::channel();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = tx.send(result);
+    });
+    gfx.device.poll(wgpu::Maintain::Wait);
+    rx.recv()
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    // Remove row padding and convert to RGBA. The texture contents are
+    // already sRGB-encoded, which is what PNG expects.
+    let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    for row in buffer_slice
+        .get_mapped_range()
+        .chunks(padded_bytes_per_row as usize)
+    {
+        pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+    }
+    buffer.unmap();
+    if swap_red_blue {
+        for pixel in pixels.chunks_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    let mut png_bytes = vec![];
+    let mut encoder = png::Encoder::new(&mut png_bytes, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|e| e.to_string())?;
+    Ok(png_bytes)
+}
+
+/// Renders the puzzle as seen by each eye into the "out" texture of `cache`
+/// and returns that texture.
+fn render_puzzle<'a>(
+    cache: &'a mut PuzzleRenderCache,
+    gfx: &GraphicsState,
+    puzzle: &mut PuzzleController,
+    prefs: &Preferences,
+    (width, height): (u32, u32),
+    eyes: &[EyeView],
+    background: wgpu::Color,
+) -> &'a wgpu::Texture {
+    let view_prefs = puzzle.view_prefs(prefs);
 
     // Create "out" texture that will ultimately be returned.
     let (out_texture, out_texture_view) = cache.out_texture.get_or_insert_with(|| {
@@ -193,7 +343,9 @@ pub(crate) fn draw_puzzle(
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: gfx.config.format,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC,
         })
     });
 
@@ -210,149 +362,315 @@ pub(crate) fn draw_puzzle(
         })
     });
 
-    // Create command encoder.
-    let mut encoder = gfx
-        .device
-        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("puzzle_command_encoder"),
-        });
-
-    // Create render pass color attachment.
-    let mut multisample_texture_view = None;
-    let render_pass_color_attachment = {
-        let clear_color = egui::Rgba::from(prefs.colors.background).to_tuple();
-        let ops = wgpu::Operations {
-            load: wgpu::LoadOp::Clear(wgpu::Color {
-                r: clear_color.0 as f64,
-                g: clear_color.1 as f64,
-                b: clear_color.2 as f64,
-                a: 1.0,
-            }),
-            store: true,
-        };
+    // Draw each eye in a separate submission so that the vertex, index, and
+    // uniform buffers can be reused.
+    for (i, eye) in eyes.iter().enumerate() {
+        let (eye_x, eye_width) = eye.viewport;
+        let scale = puzzle_scale((eye_width, height), &view_prefs);
+
+        // Generate the mesh.
+        let (mut verts, mut indices) = mesh::make_puzzle_mesh(puzzle, prefs, &eye.geometry);
+        if eye.grayscale {
+            for v in &mut verts {
+                v.color = luminance(v.color);
+            }
+        }
 
-        if prefs.gfx.msaa {
-            // Create multisample texture.
-            let (_, msaa_tex_view) = cache.multisample_texture.get_or_insert_with(|| {
-                gfx.create_texture(wgpu::TextureDescriptor {
-                    label: Some("puzzle_texture_multisample"),
-                    size: extent3d(width, height),
-                    mip_level_count: 1,
-                    sample_count: prefs.gfx.sample_count(),
-                    dimension: wgpu::TextureDimension::D2,
-                    format: gfx.config.format,
-                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                })
+        // Create command encoder.
+        let mut encoder = gfx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("puzzle_command_encoder"),
             });
 
-            // Draw to the multisample texture, then resolve it to the "out"
-            // texture.
-            wgpu::RenderPassColorAttachment {
-                view: multisample_texture_view.insert(msaa_tex_view),
-                resolve_target: Some(out_texture_view),
-                ops,
-            }
-        } else {
-            // Draw directly to the "out" texture.
-            wgpu::RenderPassColorAttachment {
-                view: out_texture_view,
-                resolve_target: None,
-                ops,
+        // Create render pass color attachment. Only the first eye clears the
+        // texture.
+        let mut multisample_texture_view = None;
+        let render_pass_color_attachment = {
+            let ops = wgpu::Operations {
+                load: if i == 0 {
+                    wgpu::LoadOp::Clear(background)
+                } else {
+                    wgpu::LoadOp::Load
+                },
+                store: true,
+            };
+
+            if prefs.gfx.msaa {
+                // Create multisample texture.
+                let (_, msaa_tex_view) = cache.multisample_texture.get_or_insert_with(|| {
+                    gfx.create_texture(wgpu::TextureDescriptor {
+                        label: Some("puzzle_texture_multisample"),
+                        size: extent3d(width, height),
+                        mip_level_count: 1,
+                        sample_count: prefs.gfx.sample_count(),
+                        dimension: wgpu::TextureDimension::D2,
+                        format: gfx.config.format,
+                        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    })
+                });
+
+                // Draw to the multisample texture, then resolve it to the
+                // "out" texture.
+                wgpu::RenderPassColorAttachment {
+                    view: multisample_texture_view.insert(msaa_tex_view),
+                    resolve_target: Some(&*out_texture_view),
+                    ops,
+                }
+            } else {
+                // Draw directly to the "out" texture.
+                wgpu::RenderPassColorAttachment {
+                    view: &*out_texture_view,
+                    resolve_target: None,
+                    ops,
+                }
             }
-        }
-    };
+        };
 
-    // Begin the render pass.
-    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-        label: Some("puzzle_stickers_render_pass"),
-        color_attachments: &[Some(render_pass_color_attachment)],
-        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-            view: depth_texture_view,
-            depth_ops: Some(wgpu::Operations {
-                load: wgpu::LoadOp::Clear(0.0),
-                store: true,
+        // Begin the render pass.
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("puzzle_stickers_render_pass"),
+            color_attachments: &[Some(render_pass_color_attachment)],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_texture_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0.0),
+                    store: true,
+                }),
+                stencil_ops: None,
             }),
-            stencil_ops: None,
-        }),
-    });
+        });
+        render_pass.set_viewport(eye_x as f32, 0.0, eye_width as f32, height as f32, 0.0, 1.0);
+
+        // Draw stickers, if there's anything to draw.
+        if !indices.is_empty() {
+            // Set pipeline.
+            let pipeline = cache
+                .basic_pipelines
+                .entry(eye.write_mask)
+                .or_insert_with(|| {
+                    create_basic_pipeline(
+                        gfx,
+                        cache.uniform_buffer.bind_group_layout(gfx),
+                        prefs.gfx.sample_count(),
+                        eye.write_mask,
+                    )
+                });
+            render_pass.set_pipeline(pipeline);
+
+            // Populate vertex buffer.
+            let vertex_buffer = cache.vertex_buffer.write_all(gfx, &mut verts);
+            render_pass.set_vertex_buffer(0, vertex_buffer);
+
+            // Populate index buffer.
+            let index_buffer = cache.index_buffer.write_all(gfx, &mut indices);
+            render_pass.set_index_buffer(index_buffer, wgpu::IndexFormat::Uint32);
+
+            // Populate and bind uniform.
+            let uniform = BasicUniform {
+                scale: scale.into(),
+                align: [view_prefs.align_h, view_prefs.align_v],
+            };
+            cache.uniform_buffer.write(gfx, &uniform);
+            render_pass.set_bind_group(0, cache.uniform_buffer.bind_group(gfx), &[]);
+
+            // Draw stickers.
+            render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+        }
 
-    // Draw stickers, if there's anything to draw.
-    if !indices.is_empty() {
-        // Set pipeline.
-        render_pass.set_pipeline(cache.basic_pipeline.get_or_insert_with(|| {
-            gfx.device
-                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                    label: Some("basic_pipeline"),
-                    layout: Some(&gfx.device.create_pipeline_layout(
-                        &wgpu::PipelineLayoutDescriptor {
-                            label: Some("basic_pipeline_layout"),
-                            bind_group_layouts: &[cache.uniform_buffer.bind_group_layout(gfx)],
-                            push_constant_ranges: &[],
-                        },
-                    )),
-                    vertex: wgpu::VertexState {
-                        module: gfx.shaders.basic.get(gfx),
-                        entry_point: "vs_main",
-                        buffers: &[RgbaVertex::LAYOUT],
-                    },
-                    primitive: wgpu::PrimitiveState {
-                        topology: wgpu::PrimitiveTopology::TriangleList,
-                        strip_index_format: None,
-                        front_face: wgpu::FrontFace::Ccw,
-                        cull_mode: None,
-                        unclipped_depth: false,
-                        polygon_mode: wgpu::PolygonMode::Fill,
-                        conservative: false,
-                    },
-                    depth_stencil: Some(wgpu::DepthStencilState {
-                        format: wgpu::TextureFormat::Depth32Float,
-                        depth_write_enabled: true,
-                        depth_compare: wgpu::CompareFunction::Greater,
-                        stencil: wgpu::StencilState::default(),
-                        bias: wgpu::DepthBiasState::default(),
-                    }),
-                    multisample: wgpu::MultisampleState {
-                        count: prefs.gfx.sample_count(),
-                        ..Default::default()
-                    },
-                    fragment: Some(wgpu::FragmentState {
-                        module: gfx.shaders.basic.get(gfx),
-                        entry_point: "fs_main",
-                        targets: &[Some(wgpu::ColorTargetState {
-                            format: gfx.config.format,
-                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                            write_mask: wgpu::ColorWrites::ALL,
-                        })],
-                    }),
-                    multiview: None,
-                })
-        }));
-
-        // Populate vertex buffer.
-        let vertex_buffer = cache.vertex_buffer.write_all(gfx, &mut verts);
-        render_pass.set_vertex_buffer(0, vertex_buffer);
-
-        // Populate index buffer.
-        let index_buffer = cache.index_buffer.write_all(gfx, &mut indices);
-        render_pass.set_index_buffer(index_buffer, wgpu::IndexFormat::Uint32);
-
-        // Populate and bind uniform.
-        let uniform = BasicUniform {
-            scale: scale.into(),
-            align: [view_prefs.align_h, view_prefs.align_v],
-        };
-        cache.uniform_buffer.write(gfx, &uniform);
-        render_pass.set_bind_group(0, cache.uniform_buffer.bind_group(gfx), &[]);
+        drop(render_pass);
 
-        // Draw stickers.
-        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+        gfx.queue.submit(std::iter::once(encoder.finish()));
     }
 
-    drop(render_pass);
+    out_texture
+}
 
-    gfx.queue.submit(std::iter::once(encoder.finish()));
+/// Puzzle geometry and render target region for one eye.
+struct EyeView {
+    geometry: Arc<Vec<ProjectedStickerGeometry>>,
+    /// Horizontal position and width of the viewport, in pixels.
+    viewport: (u32, u32),
+    /// Color channels to draw to.
+    write_mask: wgpu::ColorWrites,
+    /// Whether to convert colors to luminance before masking them, so that
+    /// each eye sees every sticker regardless of its hue.
+    grayscale: bool,
+}
+impl EyeView {
+    /// Transforms a cursor position from normalized device coordinates of the
+    /// whole render target to the coordinate space of this eye's geometry, or
+    /// returns `None` if the cursor is outside this eye's viewport.
+    fn cursor_to_puzzle_coords(
+        &self,
+        cursor_pos: cgmath::Point2<f32>,
+        (width, height): (u32, u32),
+        view_prefs: &ViewPreferences,
+    ) -> Option<cgmath::Point2<f32>> {
+        let (eye_x, eye_width) = self.viewport;
+        let cursor_x_px = (cursor_pos.x + 1.0) / 2.0 * width as f32;
+        let x = (cursor_x_px - eye_x as f32) / eye_width as f32 * 2.0 - 1.0;
+        if !(-1.0..=1.0).contains(&x) {
+            return None;
+        }
 
-    Some(out_texture.create_view(&wgpu::TextureViewDescriptor::default()))
+        let scale = puzzle_scale((eye_width, height), view_prefs);
+        Some(cgmath::point2(
+            (x - view_prefs.align_h) / scale.x,
+            (cursor_pos.y - view_prefs.align_v) / scale.y,
+        ))
+    }
+}
+
+/// Returns the views to draw according to the stereo mode.
+fn eye_views(puzzle: &mut PuzzleController, prefs: &Preferences, width: u32) -> Vec<EyeView> {
+    let mono = puzzle.geometry(prefs);
+    match effective_stereo_mode(prefs.gfx.stereo_mode, &mono) {
+        StereoMode::Off => vec![EyeView {
+            geometry: mono,
+            viewport: (0, width),
+            write_mask: wgpu::ColorWrites::ALL,
+            grayscale: false,
+        }],
+        StereoMode::SideBySide => {
+            let [left, right] = puzzle.stereo_geometry(prefs);
+            let half_width = width / 2;
+            vec![
+                EyeView {
+                    geometry: left,
+                    viewport: (0, half_width),
+                    write_mask: wgpu::ColorWrites::ALL,
+                    grayscale: false,
+                },
+                EyeView {
+                    geometry: right,
+                    viewport: (half_width, width - half_width),
+                    write_mask: wgpu::ColorWrites::ALL,
+                    grayscale: false,
+                },
+            ]
+        }
+        // Red for the left eye and cyan for the right eye.
+        StereoMode::Anaglyph => {
+            let [left, right] = puzzle.stereo_geometry(prefs);
+            vec![
+                EyeView {
+                    geometry: left,
+                    viewport: (0, width),
+                    write_mask: wgpu::ColorWrites::RED | wgpu::ColorWrites::ALPHA,
+                    grayscale: true,
+                },
+                EyeView {
+                    geometry: right,
+                    viewport: (0, width),
+                    write_mask: wgpu::ColorWrites::GREEN
+                        | wgpu::ColorWrites::BLUE
+                        | wgpu::ColorWrites::ALPHA,
+                    grayscale: true,
+                },
+            ]
+        }
+    }
+}
+
+/// Returns the stereo mode to use for the given mono geometry. Geometry with
+/// no depth has no parallax, so it is drawn in mono.
+fn effective_stereo_mode(
+    stereo_mode: StereoMode,
+    geometry: &[ProjectedStickerGeometry],
+) -> StereoMode {
+    let min_z = geometry
+        .iter()
+        .map(|geom| geom.min_bound.z)
+        .reduce(f32::min);
+    let max_z = geometry
+        .iter()
+        .map(|geom| geom.max_bound.z)
+        .reduce(f32::max);
+    match min_z.zip(max_z) {
+        Some((min_z, max_z)) if max_z - min_z > f32::EPSILON => stereo_mode,
+        _ => StereoMode::Off,
+    }
+}
+
+/// Converts a linear RGBA color to gray with the same luminance.
+fn luminance([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    [y, y, y, a]
+}
+
+fn create_basic_pipeline(
+    gfx: &GraphicsState,
+    uniform_bind_group_layout: &wgpu::BindGroupLayout,
+    sample_count: u32,
+    write_mask: wgpu::ColorWrites,
+) -> wgpu::RenderPipeline {
+    gfx.device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("basic_pipeline"),
+            layout: Some(
+                &gfx.device
+                    .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: Some("basic_pipeline_layout"),
+                        bind_group_layouts: &[uniform_bind_group_layout],
+                        push_constant_ranges: &[],
+                    }),
+            ),
+            vertex: wgpu::VertexState {
+                module: gfx.shaders.basic.get(gfx),
+                entry_point: "vs_main",
+                buffers: &[RgbaVertex::LAYOUT],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Greater,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: gfx.shaders.basic.get(gfx),
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: gfx.config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask,
+                })],
+            }),
+            multiview: None,
+        })
+}
+
+/// Returns the scale factor to convert from puzzle coordinates to normalized
+/// device coordinates.
+fn puzzle_scale((width, height): (u32, u32), view_prefs: &ViewPreferences) -> cgmath::Vector2<f32> {
+    let size = cgmath::vec2(width as f32, height as f32);
+    let min_dimen = f32::min(size.x, size.y);
+    let pixel_scale = min_dimen * view_prefs.scale;
+    cgmath::vec2(pixel_scale / size.x, pixel_scale / size.y)
+}
+
+fn background_color(prefs: &Preferences, transparent: bool) -> wgpu::Color {
+    let [r, g, b, _] = egui::Rgba::from(prefs.colors.background).to_array();
+    wgpu::Color {
+        r: r as f64,
+        g: g as f64,
+        b: b as f64,
+        a: if transparent { 0.0 } else { 1.0 },
+    }
 }
 
 fn extent3d(width: u32, height: u32) -> wgpu::Extent3d {
@@ -362,3 +680,121 @@ fn extent3d(width: u32, height: u32) -> wgpu::Extent3d {
         depth_or_array_layers: 1,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preferences::DEFAULT_PREFS;
+
+    #[test]
+    fn test_headless_capture() {
+        let gfx = match pollster::block_on(GraphicsState::new_headless()) {
+            Ok(gfx) => gfx,
+            Err(e) => {
+                // Skip this test on machines without a graphics adapter.
+                eprintln!("skipping headless capture test: {e}");
+                return;
+            }
+        };
+
+        let mut puzzle = PuzzleController::default();
+        let png_bytes =
+            capture_puzzle_image(&gfx, &mut puzzle, &DEFAULT_PREFS, (123, 45), true).unwrap();
+
+        let decoder = png::Decoder::new(png_bytes.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (123, 45));
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+
+        // The corner is transparent background, and the puzzle is opaque.
+        assert_eq!(pixels[3], 0);
+        assert!(pixels.chunks(4).any(|pixel| pixel[3] == 255));
+    }
+
+    #[test]
+    fn test_stereo_cursor_to_puzzle_coords() {
+        let mut prefs = DEFAULT_PREFS.clone();
+        prefs.gfx.stereo_mode = StereoMode::SideBySide;
+        let mut puzzle = PuzzleController::default();
+        let view_prefs = puzzle.view_prefs(&prefs).into_owned();
+        let size = (200, 100);
+
+        let eyes = eye_views(&mut puzzle, &prefs, size.0);
+        assert_eq!(eyes.len(), 2);
+        assert!(!Arc::ptr_eq(&eyes[0].geometry, &eyes[1].geometry));
+
+        // The center of each half of the target is the center of that eye.
+        let scale = puzzle_scale((100, 100), &view_prefs);
+        let expected = cgmath::point2(-view_prefs.align_h / scale.x, -view_prefs.align_v / scale.y);
+        for (eye, cursor_x) in [(&eyes[0], -0.5), (&eyes[1], 0.5)] {
+            let cursor_pos = cgmath::point2(cursor_x, 0.0);
+            let pos = eye.cursor_to_puzzle_coords(cursor_pos, size, &view_prefs);
+            assert_eq!(pos, Some(expected));
+        }
+        // Each eye ignores the cursor over the other eye.
+        let left_cursor_pos = cgmath::point2(-0.5, 0.0);
+        assert_eq!(
+            eyes[1].cursor_to_puzzle_coords(left_cursor_pos, size, &view_prefs),
+            None
+        );
+
+        // Stereo geometry is cached.
+        let eyes_again = eye_views(&mut puzzle, &prefs, size.0);
+        assert!(Arc::ptr_eq(&eyes[0].geometry, &eyes_again[0].geometry));
+    }
+
+    fn flat_geometry(z: [f32; 2]) -> Vec<ProjectedStickerGeometry> {
+        z.into_iter()
+            .enumerate()
+            .map(|(i, z)| {
+                let p = cgmath::point3(0.0, 0.0, z);
+                ProjectedStickerGeometry {
+                    sticker: crate::puzzle::Sticker(i as u16),
+                    verts: Box::new([p]),
+                    min_bound: p,
+                    max_bound: p,
+                    front_polygons: Box::new([]),
+                    back_polygons: Box::new([]),
+                    is_preview: false,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_flat_puzzle_stereo_fallback() {
+        // A flat puzzle falls back to mono.
+        for mode in [StereoMode::SideBySide, StereoMode::Anaglyph] {
+            let flat = flat_geometry([0.5, 0.5]);
+            assert_eq!(effective_stereo_mode(mode, &flat), StereoMode::Off);
+            let deep = flat_geometry([0.25, 0.75]);
+            assert_eq!(effective_stereo_mode(mode, &deep), mode);
+        }
+        assert_eq!(
+            effective_stereo_mode(StereoMode::Anaglyph, &[]),
+            StereoMode::Off,
+        );
+
+        // A 3D puzzle has depth, so both anaglyph eyes are drawn in grayscale.
+        let mut prefs = DEFAULT_PREFS.clone();
+        prefs.gfx.stereo_mode = StereoMode::Anaglyph;
+        let mut puzzle = PuzzleController::default();
+        let eyes = eye_views(&mut puzzle, &prefs, 200);
+        assert_eq!(eyes.len(), 2);
+        assert!(eyes.iter().all(|eye| eye.grayscale));
+    }
+
+    #[test]
+    fn test_anaglyph_luminance() {
+        // Pure red and pure cyan stickers are both visible to each eye.
+        for color in [[1.0, 0.0, 0.0, 1.0], [0.0, 1.0, 1.0, 1.0]] {
+            let [r, g, b, a] = luminance(color);
+            assert!(r > 0.0);
+            assert_eq!((r, a), (g, 1.0));
+            assert_eq!(g, b);
+        }
+        assert_eq!(luminance([1.0, 1.0, 1.0, 0.5]), [1.0, 1.0, 1.0, 0.5]);
+    }
+}
@@ -1,9 +1,11 @@
+use anyhow::Context;
+
 use super::shaders::Shaders;
 
-/// Graphics state for the whole window.
-pub(crate) struct GraphicsState {
+/// Graphics state for the whole window, or for offscreen rendering.
+pub struct GraphicsState {
     pub(crate) size: winit::dpi::PhysicalSize<u32>,
-    pub(crate) surface: wgpu::Surface,
+    surface: Option<wgpu::Surface>,
     pub(crate) device: wgpu::Device,
     pub(crate) queue: wgpu::Queue,
     pub(crate) config: wgpu::SurfaceConfiguration,
@@ -24,24 +26,12 @@ impl GraphicsState {
         let surface = unsafe { instance.create_surface(&window) };
 
         // Request adapter.
-        let adapter = request_adapter(&instance, &surface).await;
+        let adapter = request_adapter(&instance, Some(&surface))
+            .await
+            .expect("unable to request graphics adapter");
 
         // Request device.
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::default(),
-                    limits: if cfg!(target_arch = "wasm32") {
-                        wgpu::Limits::downlevel_webgl2_defaults()
-                    } else {
-                        wgpu::Limits::default()
-                    },
-                    label: None,
-                },
-                None,
-            )
-            .await
-            .unwrap();
+        let (device, queue) = request_device(&adapter).await.unwrap();
 
         // Configure surface.
         let config = wgpu::SurfaceConfiguration {
@@ -57,10 +47,48 @@ impl GraphicsState {
         };
         surface.configure(&device, &config);
 
-        let shaders = Shaders::new();
-
         let scale_factor = window.scale_factor() as f32;
 
+        Self::with_device(size, Some(surface), device, queue, config, scale_factor)
+    }
+
+    /// Constructs a graphics state without a window or surface, for rendering
+    /// offscreen. Render targets use an sRGB RGBA format.
+    ///
+    /// Returns an error if there is no suitable graphics adapter.
+    pub async fn new_headless() -> anyhow::Result<Self> {
+        let size = winit::dpi::PhysicalSize::new(1, 1);
+
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let adapter = request_adapter(&instance, None)
+            .await
+            .context("unable to request graphics adapter")?;
+        let (device, queue) = request_device(&adapter).await?;
+
+        // There is no surface to configure, but the configuration still
+        // determines the format of render targets.
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+        };
+
+        Ok(Self::with_device(size, None, device, queue, config, 1.0))
+    }
+
+    fn with_device(
+        size: winit::dpi::PhysicalSize<u32>,
+        surface: Option<wgpu::Surface>,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        config: wgpu::SurfaceConfiguration,
+        scale_factor: f32,
+    ) -> Self {
+        let shaders = Shaders::new();
+
         let dummy_texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("dummy_texture"),
             size: wgpu::Extent3d::default(),
@@ -86,12 +114,25 @@ impl GraphicsState {
         }
     }
 
+    /// Returns the window surface.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the graphics state was constructed using `new_headless()`.
+    pub(crate) fn surface(&self) -> &wgpu::Surface {
+        self.surface
+            .as_ref()
+            .expect("headless graphics state has no surface")
+    }
+
     pub(crate) fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
             self.size = new_size;
             self.config.width = new_size.width;
             self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
+            if let Some(surface) = &self.surface {
+                surface.configure(&self.device, &self.config);
+            }
         }
     }
 
@@ -180,20 +221,38 @@ impl GraphicsState {
     }
 }
 
-async fn request_adapter(instance: &wgpu::Instance, surface: &wgpu::Surface) -> wgpu::Adapter {
+async fn request_adapter(
+    instance: &wgpu::Instance,
+    surface: Option<&wgpu::Surface>,
+) -> Option<wgpu::Adapter> {
     let mut opts = wgpu::RequestAdapterOptions {
         power_preference: wgpu::PowerPreference::HighPerformance,
-        compatible_surface: Some(surface),
+        compatible_surface: surface,
         force_fallback_adapter: false,
     };
 
     if let Some(adapter) = instance.request_adapter(&opts).await {
-        return adapter;
+        return Some(adapter);
     }
     opts.force_fallback_adapter = true;
-    if let Some(adapter) = instance.request_adapter(&opts).await {
-        return adapter;
-    }
+    instance.request_adapter(&opts).await
+}
 
-    panic!("unable to request graphics adapter")
+async fn request_device(
+    adapter: &wgpu::Adapter,
+) -> Result<(wgpu::Device, wgpu::Queue), wgpu::RequestDeviceError> {
+    adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                features: wgpu::Features::default(),
+                limits: if cfg!(target_arch = "wasm32") {
+                    wgpu::Limits::downlevel_webgl2_defaults()
+                } else {
+                    wgpu::Limits::default()
+                },
+                label: None,
+            },
+            None,
+        )
+        .await
 }